@@ -1,8 +1,11 @@
 use crate::{
     error::SolverError,
     instruction::{
-        execute_order::process_execute_order, initialize_order::process_initialize_order,
-        Instruction,
+        cancel_order::process_cancel_order, execute_order::process_execute_order,
+        execute_signed_order::process_execute_signed_order,
+        initialize_config::process_initialize_config, initialize_order::process_initialize_order,
+        set_paused::process_set_paused, update_allow_list::process_update_allow_list,
+        update_config::process_update_config, Instruction,
     },
 };
 use pinocchio::{account_info::AccountInfo, entrypoint, msg, pubkey::Pubkey, ProgramResult};
@@ -23,6 +26,12 @@ pub fn process_instruction(
     let instruction = Instruction::try_from(*discriminator)?;
     match instruction {
         Instruction::Initialize => process_initialize_order(accounts, instruction_data),
+        Instruction::Cancel => process_cancel_order(accounts, instruction_data),
         Instruction::Execute => process_execute_order(accounts, instruction_data),
+        Instruction::InitializeConfig => process_initialize_config(accounts, instruction_data),
+        Instruction::UpdateConfig => process_update_config(accounts, instruction_data),
+        Instruction::SetPaused => process_set_paused(accounts, instruction_data),
+        Instruction::ExecuteSigned => process_execute_signed_order(accounts, instruction_data),
+        Instruction::UpdateAllowList => process_update_allow_list(accounts, instruction_data),
     }
 }
@@ -14,6 +14,40 @@ pub enum SolverError {
     InvalidOrderAccountOwner,
     InvalidRentPayer,
     SlippageExceeded,
+    InvalidOrderMode,
+    InvalidReferralFee,
+    InvalidConfigAccount,
+    InvalidProtocolFee,
+    InvalidAdmin,
+    ProgramPaused,
+    InvalidIntentSignature,
+    OrderAlreadyFilled,
+    InvalidNonceAccount,
+    InvalidFillAmount,
+    OrderExpired,
+    OrderNotStarted,
+    InvalidAuctionCurve,
+    InvalidCurveParameters,
+    InvalidOrderType,
+    InvalidOracleAccount,
+    OraclePriceStale,
+    OraclePriceDeviation,
+    InvalidOracleGuard,
+    InvalidAllowListAccount,
+    ProgramNotAllowed,
+    InvalidRoute,
+    OverSpent,
+    InvalidTokenProgram,
+    InvalidUnwrapAccount,
+    InvalidTokenAccount,
+    InvalidPda,
+    AccountMustBeMut,
+    DuplicateAccount,
+    InvalidProgramId,
+    InvalidProgramData,
+    SignRouteNotAllowed,
+    SellAuthorityChanged,
+    OracleFeedMismatch,
 }
 
 impl From<SolverError> for ProgramError {
@@ -38,6 +72,42 @@ impl ToStr for SolverError {
             SolverError::InvalidOrderAccountOwner => "Invalid order account owner",
             SolverError::InvalidRentPayer => "Invalid rent payer",
             SolverError::SlippageExceeded => "Slippage exceeded",
+            SolverError::InvalidOrderMode => "Invalid order mode",
+            SolverError::InvalidReferralFee => "Invalid referral fee",
+            SolverError::InvalidConfigAccount => "Invalid config account",
+            SolverError::InvalidProtocolFee => "Invalid protocol fee",
+            SolverError::InvalidAdmin => "Invalid admin",
+            SolverError::ProgramPaused => "Program is paused",
+            SolverError::InvalidIntentSignature => "Invalid intent signature",
+            SolverError::OrderAlreadyFilled => "Order already filled",
+            SolverError::InvalidNonceAccount => "Invalid nonce account",
+            SolverError::InvalidFillAmount => "Invalid fill amount",
+            SolverError::OrderExpired => "Order expired",
+            SolverError::OrderNotStarted => "Order not started",
+            SolverError::InvalidAuctionCurve => "Invalid auction curve",
+            SolverError::InvalidCurveParameters => "Invalid curve parameters",
+            SolverError::InvalidOrderType => "Invalid order type",
+            SolverError::InvalidOracleAccount => "Invalid oracle account",
+            SolverError::OraclePriceStale => "Oracle price is stale",
+            SolverError::OraclePriceDeviation => "Fill deviates too far from the oracle price",
+            SolverError::InvalidOracleGuard => "Invalid oracle guard",
+            SolverError::InvalidAllowListAccount => "Invalid allow-list account",
+            SolverError::ProgramNotAllowed => "Program is not in the allow-list",
+            SolverError::InvalidRoute => "Invalid route",
+            SolverError::OverSpent => "Sell side spent more than the fill",
+            SolverError::InvalidTokenProgram => "Invalid token program",
+            SolverError::InvalidUnwrapAccount => "Invalid native SOL unwrap account",
+            SolverError::InvalidTokenAccount => "Invalid token account",
+            SolverError::InvalidPda => "Invalid program derived address",
+            SolverError::AccountMustBeMut => "Account must be mutable",
+            SolverError::DuplicateAccount => "Account passed more than once",
+            SolverError::InvalidProgramId => "Invalid program id",
+            SolverError::InvalidProgramData => "Invalid program data account",
+            SolverError::SignRouteNotAllowed => {
+                "Signed orders can't lend their authority to the route"
+            }
+            SolverError::SellAuthorityChanged => "Route changed the authorities of the sell source",
+            SolverError::OracleFeedMismatch => "Oracle account holds another price feed",
         }
     }
 }
@@ -71,6 +141,66 @@ impl TryFrom<u32> for SolverError {
             }
             x if x == SolverError::InvalidRentPayer as u32 => Ok(SolverError::InvalidRentPayer),
             x if x == SolverError::SlippageExceeded as u32 => Ok(SolverError::SlippageExceeded),
+            x if x == SolverError::InvalidOrderMode as u32 => Ok(SolverError::InvalidOrderMode),
+            x if x == SolverError::InvalidReferralFee as u32 => Ok(SolverError::InvalidReferralFee),
+            x if x == SolverError::InvalidConfigAccount as u32 => {
+                Ok(SolverError::InvalidConfigAccount)
+            }
+            x if x == SolverError::InvalidProtocolFee as u32 => Ok(SolverError::InvalidProtocolFee),
+            x if x == SolverError::InvalidAdmin as u32 => Ok(SolverError::InvalidAdmin),
+            x if x == SolverError::ProgramPaused as u32 => Ok(SolverError::ProgramPaused),
+            x if x == SolverError::InvalidIntentSignature as u32 => {
+                Ok(SolverError::InvalidIntentSignature)
+            }
+            x if x == SolverError::OrderAlreadyFilled as u32 => Ok(SolverError::OrderAlreadyFilled),
+            x if x == SolverError::InvalidNonceAccount as u32 => {
+                Ok(SolverError::InvalidNonceAccount)
+            }
+            x if x == SolverError::InvalidFillAmount as u32 => Ok(SolverError::InvalidFillAmount),
+            x if x == SolverError::OrderExpired as u32 => Ok(SolverError::OrderExpired),
+            x if x == SolverError::OrderNotStarted as u32 => Ok(SolverError::OrderNotStarted),
+            x if x == SolverError::InvalidAuctionCurve as u32 => {
+                Ok(SolverError::InvalidAuctionCurve)
+            }
+            x if x == SolverError::InvalidCurveParameters as u32 => {
+                Ok(SolverError::InvalidCurveParameters)
+            }
+            x if x == SolverError::InvalidOrderType as u32 => Ok(SolverError::InvalidOrderType),
+            x if x == SolverError::InvalidOracleAccount as u32 => {
+                Ok(SolverError::InvalidOracleAccount)
+            }
+            x if x == SolverError::OraclePriceStale as u32 => Ok(SolverError::OraclePriceStale),
+            x if x == SolverError::OraclePriceDeviation as u32 => {
+                Ok(SolverError::OraclePriceDeviation)
+            }
+            x if x == SolverError::InvalidOracleGuard as u32 => Ok(SolverError::InvalidOracleGuard),
+            x if x == SolverError::InvalidAllowListAccount as u32 => {
+                Ok(SolverError::InvalidAllowListAccount)
+            }
+            x if x == SolverError::ProgramNotAllowed as u32 => Ok(SolverError::ProgramNotAllowed),
+            x if x == SolverError::InvalidRoute as u32 => Ok(SolverError::InvalidRoute),
+            x if x == SolverError::OverSpent as u32 => Ok(SolverError::OverSpent),
+            x if x == SolverError::InvalidTokenProgram as u32 => {
+                Ok(SolverError::InvalidTokenProgram)
+            }
+            x if x == SolverError::InvalidUnwrapAccount as u32 => {
+                Ok(SolverError::InvalidUnwrapAccount)
+            }
+            x if x == SolverError::InvalidTokenAccount as u32 => {
+                Ok(SolverError::InvalidTokenAccount)
+            }
+            x if x == SolverError::InvalidPda as u32 => Ok(SolverError::InvalidPda),
+            x if x == SolverError::AccountMustBeMut as u32 => Ok(SolverError::AccountMustBeMut),
+            x if x == SolverError::DuplicateAccount as u32 => Ok(SolverError::DuplicateAccount),
+            x if x == SolverError::InvalidProgramId as u32 => Ok(SolverError::InvalidProgramId),
+            x if x == SolverError::InvalidProgramData as u32 => Ok(SolverError::InvalidProgramData),
+            x if x == SolverError::SignRouteNotAllowed as u32 => {
+                Ok(SolverError::SignRouteNotAllowed)
+            }
+            x if x == SolverError::SellAuthorityChanged as u32 => {
+                Ok(SolverError::SellAuthorityChanged)
+            }
+            x if x == SolverError::OracleFeedMismatch as u32 => Ok(SolverError::OracleFeedMismatch),
             _ => Err(ProgramError::Custom(value)),
         }
     }
@@ -0,0 +1,137 @@
+use crate::utils::accounts::{check_distinct, check_signer, check_writable};
+use crate::utils::token::{
+    check_token_program, load_mint, load_token_account, CloseAccount, Revoke, TransferChecked,
+};
+use crate::utils::{close_program_account, DataLen};
+use crate::{
+    error::SolverError,
+    state::order::{Order, OrderMode},
+};
+use pinocchio::instruction::Signer;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+pub struct CancelOrderContext<'a> {
+    pub owner: &'a AccountInfo,
+    pub order_account: &'a AccountInfo,
+    pub from_token_account: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub sell_mint: &'a AccountInfo,
+    /// Extra accounts of the sell mint's transfer hook, if any.
+    pub hook_accounts: &'a [AccountInfo],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CancelOrderContext<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [owner, order_account, from_token_account, vault, token_program, sell_mint, hook_accounts @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        check_signer(owner)?;
+        check_writable(owner, SolverError::AccountMustBeMut)?;
+        check_writable(order_account, SolverError::OrderAccountMustBeMut)?;
+        check_writable(from_token_account, SolverError::AccountMustBeMut)?;
+        check_token_program(token_program)?;
+        check_distinct(&[order_account, from_token_account, vault])?;
+
+        Ok(Self {
+            owner,
+            order_account,
+            from_token_account,
+            vault,
+            token_program,
+            sell_mint,
+            hook_accounts,
+        })
+    }
+}
+
+pub fn process_cancel_order(accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
+    let context = CancelOrderContext::try_from(accounts)?;
+
+    // 1. Parse arguments (bump + Order)
+    if args.len() != 1 + Order::LEN {
+        return Err(SolverError::InvalidInstructionData.into());
+    }
+
+    let order_bump = args[0];
+    let order_data = &args[1..];
+
+    // The PDA is derived from the signing owner, so only the maker can cancel
+    let (order, intent_hash) = Order::validate_and_unpack(
+        order_data,
+        context.owner.key(),
+        context.order_account.key(),
+        order_bump,
+    )?;
+
+    if context.from_token_account.key() != &order.from_token_account {
+        return Err(SolverError::InvalidOrderAccounts.into());
+    }
+
+    // 2. Release the sell tokens back to the owner
+    match order.mode()? {
+        OrderMode::Escrow => {
+            // Return the escrowed sell tokens and close the vault
+            let vault_amount = {
+                let vault = load_token_account(context.vault)?;
+                if vault.owner() != context.order_account.key() {
+                    return Err(SolverError::InvalidTokenAccountOwner.into());
+                }
+                vault.amount()
+            };
+
+            let seeds = Order::signer_seeds(context.owner.key(), &intent_hash, &order_bump);
+
+            // Closing a wrapped SOL vault already hands its lamports back to the owner
+            if vault_amount > 0 && order.native_sell == 0 {
+                TransferChecked {
+                    from: context.vault,
+                    mint: context.sell_mint,
+                    to: context.from_token_account,
+                    authority: context.order_account,
+                    amount: vault_amount,
+                    decimals: load_mint(context.sell_mint)?.decimals(),
+                    token_program: context.token_program,
+                    hook_accounts: context.hook_accounts,
+                }
+                .invoke_signed(&[Signer::from(&seeds)])?;
+            }
+
+            CloseAccount {
+                account: context.vault,
+                destination: context.owner,
+                authority: context.order_account,
+                token_program: context.token_program,
+            }
+            .invoke_signed(&[Signer::from(&seeds)])?;
+        }
+        OrderMode::Delegate => {
+            // Revoke the delegation the order PDA holds over the sell tokens, if any
+            let delegated_to_order = {
+                let token_account = load_token_account(context.from_token_account)?;
+                token_account.delegate() == Some(context.order_account.key())
+            };
+
+            if delegated_to_order {
+                Revoke {
+                    source: context.from_token_account,
+                    authority: context.owner,
+                    token_program: context.token_program,
+                }
+                .invoke()?;
+            }
+        }
+    }
+
+    // 3. Close the order account and refund its rent to the owner
+    if context.order_account.is_owned_by(&crate::ID) {
+        close_program_account(context.order_account, context.owner)?;
+    }
+
+    Ok(())
+}
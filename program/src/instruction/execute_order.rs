@@ -1,13 +1,30 @@
 use crate::error::SolverError;
-use crate::state::order::Order;
-use crate::utils::DataLen;
+use crate::instruction::route::parse_route;
+use crate::state::allow_list::AllowList;
+use crate::state::config::Config;
+use crate::state::nonce::NonceBitmap;
+use crate::state::oracle::OraclePrice;
+use crate::state::order::{Order, OrderAccount, OrderMode};
+use crate::utils::accounts::{
+    check_distinct, check_owner, check_program, check_signer, check_writable,
+};
+use crate::utils::token::{
+    check_token_program, load_mint, load_token_account, CloseAccount, TransferChecked,
+    ASSOCIATED_TOKEN_PROGRAM_ID, NATIVE_MINT,
+};
+use crate::utils::{close_program_account, load_acc_mut_unchecked, load_acc_unchecked, DataLen};
 use alloc::vec::Vec;
-use pinocchio::cpi::slice_invoke_signed;
+use pinocchio::cpi::{slice_invoke, slice_invoke_signed};
 use pinocchio::instruction::{AccountMeta, Instruction, Seed, Signer};
 use pinocchio::sysvars::clock::Clock;
 use pinocchio::sysvars::Sysvar;
-use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
-use pinocchio_token::state::TokenAccount;
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::{try_find_program_address, Pubkey},
+    ProgramResult,
+};
+use pinocchio_system::instructions::Transfer as SystemTransfer;
 
 pub struct ExecuteOrderContext<'a> {
     pub solver: &'a AccountInfo,
@@ -16,8 +33,15 @@ pub struct ExecuteOrderContext<'a> {
     pub from_token_account: &'a AccountInfo,
     pub to_token_account: &'a AccountInfo,
     pub referral_token_account: &'a AccountInfo,
-    pub order_program: &'a AccountInfo,
     pub token_program: &'a AccountInfo,
+    pub sell_mint: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub solver_token_account: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub treasury_token_account: &'a AccountInfo,
+    pub nonce_account: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub allow_list: &'a AccountInfo,
     pub remaining_accounts: &'a [AccountInfo],
 }
 
@@ -25,15 +49,36 @@ impl<'a> TryFrom<&'a [AccountInfo]> for ExecuteOrderContext<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [solver, order_account, owner, from_token_account, to_token_account, referral_token_account, order_program, token_program, remaining_accounts @ ..] =
+        let [solver, order_account, owner, from_token_account, to_token_account, referral_token_account, token_program, sell_mint, vault, solver_token_account, config, treasury_token_account, nonce_account, system_program, allow_list, remaining_accounts @ ..] =
             accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
-        if !solver.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
+        // Shared with `ExecuteSigned`, where the order account is the read-only authority PDA
+        check_signer(solver)?;
+        check_writable(solver, SolverError::AccountMustBeMut)?;
+        for account in [
+            from_token_account,
+            to_token_account,
+            solver_token_account,
+            nonce_account,
+        ] {
+            check_writable(account, SolverError::AccountMustBeMut)?;
         }
+        check_token_program(token_program)?;
+        check_program(system_program, &pinocchio_system::ID)?;
+        check_distinct(&[
+            solver,
+            order_account,
+            from_token_account,
+            to_token_account,
+            vault,
+            solver_token_account,
+            config,
+            nonce_account,
+            allow_list,
+        ])?;
 
         Ok(Self {
             solver,
@@ -42,8 +87,15 @@ impl<'a> TryFrom<&'a [AccountInfo]> for ExecuteOrderContext<'a> {
             from_token_account,
             to_token_account,
             referral_token_account,
-            order_program,
             token_program,
+            sell_mint,
+            vault,
+            solver_token_account,
+            config,
+            treasury_token_account,
+            nonce_account,
+            system_program,
+            allow_list,
             remaining_accounts,
         })
     }
@@ -51,14 +103,31 @@ impl<'a> TryFrom<&'a [AccountInfo]> for ExecuteOrderContext<'a> {
 
 pub fn process_execute_order(accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
     let context = ExecuteOrderContext::try_from(accounts)?;
+    check_writable(context.order_account, SolverError::OrderAccountMustBeMut)?;
+    check_owner(
+        context.order_account,
+        &crate::ID,
+        SolverError::InvalidOrderAccountOwner,
+    )?;
+    // The owner collects the order account's rent once it is filled
+    check_writable(context.owner, SolverError::AccountMustBeMut)?;
+
+    let config = Config::load(context.config)?;
+    config.check_not_paused()?;
 
-    // 1. Parse arguments (bump + Order + CPI data)
-    if args.len() < 1 + Order::LEN {
+    // 1. Parse arguments (bump + Order + fill amount + route data)
+    if args.len() < 1 + Order::LEN + 8 {
         return Err(SolverError::InvalidInstructionData.into());
     }
 
     let order_bump = args[0];
     let order_data = &args[1..1 + Order::LEN];
+    let (fill_amount, route_data) = args[1 + Order::LEN..].split_at(8);
+    let fill_amount = u64::from_le_bytes(
+        fill_amount
+            .try_into()
+            .map_err(|_| ProgramError::from(SolverError::InvalidInstructionData))?,
+    );
 
     let (order, intend_hash) = Order::validate_and_unpack(
         order_data,
@@ -74,54 +143,376 @@ pub fn process_execute_order(accounts: &[AccountInfo], args: &[u8]) -> ProgramRe
     ) {
         return Err(SolverError::InvalidOrderAccounts.into());
     }
-    let clock = Clock::get()?;
-    let expected_buy_amount = order.calculate_current_buy_amount(clock.unix_timestamp as u64);
 
-    let pre_balance = {
-        let token_account = TokenAccount::from_account_info(context.to_token_account).unwrap();
-        token_account.amount()
+    let filled_sell_amount = {
+        let data = context.order_account.try_borrow_data()?;
+        let order_account = unsafe { load_acc_unchecked::<OrderAccount>(&data) }
+            .map_err(|_| ProgramError::from(SolverError::InvalidOrderAccount))?;
+        if order_account.order != order {
+            return Err(SolverError::InvalidOrderAccount.into());
+        }
+        order_account.filled_sell_amount
     };
 
-    // TODO: Add fee handling
+    let seeds = Order::signer_seeds(context.owner.key(), &intend_hash, &order_bump);
+
+    fill_order(
+        &context,
+        &config,
+        &order,
+        &seeds,
+        filled_sell_amount,
+        fill_amount,
+        route_data,
+    )?;
+
+    // 4. Track the fill, closing the order once it is fully filled
+    let filled_sell_amount = filled_sell_amount
+        .checked_add(fill_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    if filled_sell_amount < order.sell_amount {
+        let mut data = context.order_account.try_borrow_mut_data()?;
+        unsafe { load_acc_mut_unchecked::<OrderAccount>(&mut data)? }.filled_sell_amount =
+            filled_sell_amount;
+        return Ok(());
+    }
 
-    // Remaining data is CPI instruction data
-    let instruction_data = &args[1 + Order::LEN..];
+    if order.mode()? == OrderMode::Escrow && load_token_account(context.vault)?.amount() == 0 {
+        CloseAccount {
+            account: context.vault,
+            destination: context.owner,
+            authority: context.order_account,
+            token_program: context.token_program,
+        }
+        .invoke_signed(&[Signer::from(&seeds)])?;
+    }
 
-    let instruction = Instruction {
-        program_id: context.order_program.key(),
-        accounts: &context
+    close_program_account(context.order_account, context.owner)
+}
+
+/// The wSOL associated token account of the order signer a native buy is delivered to,
+/// with the SPL Token program owning it. wSOL is an SPL Token mint whatever program the
+/// sell mint belongs to.
+fn find_unwrap_account<'a>(
+    context: &ExecuteOrderContext<'a>,
+) -> Result<(&'a AccountInfo, &'a AccountInfo), ProgramError> {
+    let (unwrap_key, _) = try_find_program_address(
+        &[
+            context.order_account.key().as_ref(),
+            pinocchio_token::ID.as_ref(),
+            NATIVE_MINT.as_ref(),
+        ],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .ok_or(SolverError::InvalidUnwrapAccount)?;
+
+    let find_account = |key: &Pubkey| {
+        context
             .remaining_accounts
             .iter()
-            .map(|acc| AccountMeta {
-                pubkey: acc.key(),
-                is_signer: acc.is_signer(),
-                is_writable: acc.is_writable(),
-            })
-            .collect::<Vec<AccountMeta>>(),
-        data: instruction_data,
+            .find(|account| account.key() == key)
+            .ok_or(SolverError::InvalidUnwrapAccount)
+    };
+    let unwrap_account = find_account(&unwrap_key)?;
+    let token_program = find_account(&pinocchio_token::ID)?;
+
+    if !unwrap_account.is_owned_by(&pinocchio_token::ID) {
+        return Err(SolverError::InvalidUnwrapAccount.into());
+    }
+    let token_account = load_token_account(unwrap_account)?;
+    if token_account.mint() != &NATIVE_MINT || token_account.owner() != context.order_account.key()
+    {
+        return Err(SolverError::InvalidUnwrapAccount.into());
+    }
+
+    Ok((unwrap_account, token_program))
+}
+
+/// Owner, delegate and close authority of a token account.
+fn token_authorities(
+    account: &AccountInfo,
+) -> Result<(Pubkey, Option<Pubkey>, Option<Pubkey>), ProgramError> {
+    let token_account = load_token_account(account)?;
+    Ok((
+        *token_account.owner(),
+        token_account.delegate().copied(),
+        token_account.close_authority().copied(),
+    ))
+}
+
+/// Pays `fill_amount` sell tokens out of the order, runs the solver's route and checks that
+/// the owner received at least the pro-rata buy amount. `signer_seeds` are the seeds of the
+/// PDA that has authority over the sell tokens.
+pub(crate) fn fill_order(
+    context: &ExecuteOrderContext,
+    config: &Config,
+    order: &Order,
+    signer_seeds: &[Seed],
+    filled_sell_amount: u64,
+    fill_amount: u64,
+    route_data: &[u8],
+) -> ProgramResult {
+    order.check_fill_amount(filled_sell_amount, fill_amount)?;
+    order.check_native_accounts(context.owner.key())?;
+    order.check_token_accounts(
+        context.owner.key(),
+        context.sell_mint,
+        context.from_token_account,
+        context.to_token_account,
+        context.referral_token_account,
+    )?;
+
+    // Every hop may act on the order's behalf, so only vetted programs are invoked
+    let route = parse_route(route_data)?;
+    let hook_accounts = route.hook_accounts(context.remaining_accounts)?;
+    let allow_list = AllowList::load(context.allow_list)?;
+    for hop in &route.hops {
+        let (program, _) = hop.resolve(context.remaining_accounts)?;
+        allow_list.check_allowed(program.key())?;
+    }
+
+    NonceBitmap::check_unused(
+        context.nonce_account,
+        context.owner.key(),
+        context.solver,
+        order.nonce,
+    )?;
+
+    let clock = Clock::get()?;
+    let current_time = clock.unix_timestamp as u64;
+    order.check_time_window(current_time)?;
+    let expected_buy_amount = order.required_buy_amount(current_time, fill_amount)?;
+
+    // Native SOL is bought as wSOL into the order signer's account, then unwrapped
+    let unwrap_account = if order.native_buy != 0 {
+        Some(find_unwrap_account(context)?)
+    } else {
+        None
+    };
+
+    let pre_balance = if unwrap_account.is_some() {
+        context.to_token_account.lamports()
+    } else {
+        load_token_account(context.to_token_account)?.amount()
+    };
+
+    // 2. Pay the solver the sell side, so the swap is two-sided
+    {
+        let solver_token_account = load_token_account(context.solver_token_account)?;
+        if solver_token_account.mint() != context.sell_mint.key() {
+            return Err(SolverError::InvalidTokenAccountMint.into());
+        }
+    }
+
+    let sell_source = match order.mode()? {
+        OrderMode::Escrow => {
+            check_writable(context.vault, SolverError::AccountMustBeMut)?;
+            let vault = load_token_account(context.vault)?;
+            if vault.owner() != context.order_account.key()
+                || vault.has_delegate()
+                || vault.has_close_authority()
+            {
+                return Err(SolverError::InvalidTokenAccountOwner.into());
+            }
+            context.vault
+        }
+        // The approval covers exactly `sell_amount`, so the fills consume it
+        OrderMode::Delegate => context.from_token_account,
     };
 
-    let seeds = [
-        Seed::from(b"order".as_slice()),
-        Seed::from(context.owner.key()),
-        Seed::from(intend_hash.as_ref()),
-        Seed::from(core::slice::from_ref(&order_bump)),
-    ];
+    let sell_pre_balance = load_token_account(sell_source)?.amount();
+    let sell_authorities = token_authorities(sell_source)?;
+
+    let decimals = load_mint(context.sell_mint)?.decimals();
+
+    // Fees are carved out of the sell side, the solver receives the rest
+    let referral_fee = order.referral_fee_amount(filled_sell_amount, fill_amount)?;
+    let protocol_fee = config.protocol_fee_amount(fill_amount)?;
+    let solver_amount = fill_amount
+        .checked_sub(referral_fee)
+        .and_then(|amount| amount.checked_sub(protocol_fee))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    // Fee recipients are only checked when paid, and may not stand in for the sell
+    // source or the solver, who would collect the fee on top of its share
+    let mut sell_side_accounts = Vec::from([
+        context.from_token_account,
+        context.vault,
+        context.solver_token_account,
+    ]);
+    if referral_fee > 0 {
+        check_writable(
+            context.referral_token_account,
+            SolverError::AccountMustBeMut,
+        )?;
+        sell_side_accounts.push(context.referral_token_account);
+    }
+    if protocol_fee > 0 {
+        check_writable(
+            context.treasury_token_account,
+            SolverError::AccountMustBeMut,
+        )?;
+        sell_side_accounts.push(context.treasury_token_account);
+    }
+    check_distinct(&sell_side_accounts)?;
+
+    if referral_fee > 0 {
+        TransferChecked {
+            from: sell_source,
+            mint: context.sell_mint,
+            to: context.referral_token_account,
+            authority: context.order_account,
+            amount: referral_fee,
+            decimals,
+            token_program: context.token_program,
+            hook_accounts,
+        }
+        .invoke_signed(&[Signer::from(signer_seeds)])?;
+    }
+
+    if protocol_fee > 0 {
+        {
+            let treasury_token_account = load_token_account(context.treasury_token_account)?;
+            if treasury_token_account.owner() != &config.treasury {
+                return Err(SolverError::InvalidTokenAccountOwner.into());
+            }
+            if treasury_token_account.mint() != context.sell_mint.key() {
+                return Err(SolverError::InvalidTokenAccountMint.into());
+            }
+        }
 
-    let signer = Signer::from(&seeds);
+        TransferChecked {
+            from: sell_source,
+            mint: context.sell_mint,
+            to: context.treasury_token_account,
+            authority: context.order_account,
+            amount: protocol_fee,
+            decimals,
+            token_program: context.token_program,
+            hook_accounts,
+        }
+        .invoke_signed(&[Signer::from(signer_seeds)])?;
+    }
 
-    let account_refs: Vec<&AccountInfo> = context.remaining_accounts.iter().collect();
+    TransferChecked {
+        from: sell_source,
+        mint: context.sell_mint,
+        to: context.solver_token_account,
+        authority: context.order_account,
+        amount: solver_amount,
+        decimals,
+        token_program: context.token_program,
+        hook_accounts,
+    }
+    .invoke_signed(&[Signer::from(signer_seeds)])?;
 
-    slice_invoke_signed(&instruction, &account_refs, &[signer]).unwrap();
+    // 3. Run the solver's route, one CPI per hop. All token movements the order authorizes
+    // happened above, so hops only get the order's signer authority when the owner opted in.
+    let sign_route = order.sign_route != 0;
 
-    let post_balance = {
-        let token_account = TokenAccount::from_account_info(context.to_token_account).unwrap();
-        token_account.amount()
+    for hop in &route.hops {
+        let (program, accounts) = hop.resolve(context.remaining_accounts)?;
+
+        let instruction = Instruction {
+            program_id: program.key(),
+            accounts: &accounts
+                .iter()
+                .map(|acc| AccountMeta {
+                    pubkey: acc.key(),
+                    is_signer: acc.is_signer()
+                        || (sign_route && acc.key() == context.order_account.key()),
+                    is_writable: acc.is_writable(),
+                })
+                .collect::<Vec<AccountMeta>>(),
+            data: hop.data,
+        };
+
+        let account_refs: Vec<&AccountInfo> = accounts.iter().collect();
+
+        if sign_route {
+            slice_invoke_signed(&instruction, &account_refs, &[Signer::from(signer_seeds)])?;
+        } else {
+            slice_invoke(&instruction, &account_refs)?;
+        }
+    }
+
+    // A signed hop could hand the sell source over, or approve a spender, without moving
+    // any of its balance
+    if sign_route && token_authorities(sell_source)? != sell_authorities {
+        return Err(SolverError::SellAuthorityChanged.into());
+    }
+
+    // The sell side may only lose what this fill releases, whatever the route did with it
+    let sell_post_balance = load_token_account(sell_source)?.amount();
+    if sell_pre_balance.saturating_sub(sell_post_balance) > fill_amount {
+        return Err(SolverError::OverSpent.into());
+    }
+
+    let post_balance = if let Some((unwrap_account, unwrap_token_program)) = unwrap_account {
+        let unwrapped_amount = load_token_account(unwrap_account)?.amount();
+
+        // The solver funded the account, so it gets the rent back and the owner the SOL
+        CloseAccount {
+            account: unwrap_account,
+            destination: context.solver,
+            authority: context.order_account,
+            token_program: unwrap_token_program,
+        }
+        .invoke_signed(&[Signer::from(signer_seeds)])?;
+
+        if unwrapped_amount > 0 {
+            SystemTransfer {
+                from: context.solver,
+                to: context.to_token_account,
+                lamports: unwrapped_amount,
+            }
+            .invoke()?;
+        }
+
+        context.to_token_account.lamports()
+    } else {
+        load_token_account(context.to_token_account)?.amount()
     };
 
-    if post_balance < pre_balance + expected_buy_amount {
+    // Token-2022 withholds transfer fees on the destination, outside of `amount`, so the
+    // owner has to receive the buy amount net of fees
+    let required_balance = pre_balance
+        .checked_add(expected_buy_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    if post_balance < required_balance {
         return Err(SolverError::SlippageExceeded.into());
     }
 
+    // 4. Check the fill against the oracle, if the owner asked for it
+    if order.oracle != Pubkey::default() {
+        let oracle = context
+            .remaining_accounts
+            .iter()
+            .find(|account| account.key() == &order.oracle)
+            .ok_or(SolverError::InvalidOracleAccount)?;
+
+        let buy_mint = context
+            .remaining_accounts
+            .iter()
+            .find(|account| account.key() == &order.buy_mint)
+            .ok_or(SolverError::InvalidTokenAccountMint)?;
+        let buy_decimals = load_mint(buy_mint)?.decimals();
+
+        let price = OraclePrice::load(oracle, &order.oracle_feed_id)?;
+        price.check_fresh(clock.unix_timestamp, order.oracle_max_staleness)?;
+        price.check_deviation(
+            fill_amount,
+            post_balance - pre_balance,
+            order.oracle_max_deviation_bps,
+            decimals,
+            buy_decimals,
+        )?;
+    }
+
+    if fill_amount == order.sell_amount - filled_sell_amount {
+        NonceBitmap::mark(context.nonce_account, order.nonce)?;
+    }
+
     Ok(())
 }
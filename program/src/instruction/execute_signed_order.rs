@@ -0,0 +1,94 @@
+use crate::error::SolverError;
+use crate::instruction::execute_order::{fill_order, ExecuteOrderContext};
+use crate::state::config::Config;
+use crate::state::order::{Order, OrderMode};
+use crate::utils::ed25519::verify_ed25519_instruction;
+use crate::utils::DataLen;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+/// Accounts of `ExecuteSigned`: the Instructions sysvar followed by the accounts of
+/// `Execute`, where the order account is the owner's authority PDA.
+pub struct ExecuteSignedOrderContext<'a> {
+    pub instructions_sysvar: &'a AccountInfo,
+    pub execute: ExecuteOrderContext<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ExecuteSignedOrderContext<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [instructions_sysvar, accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        Ok(Self {
+            instructions_sysvar,
+            execute: ExecuteOrderContext::try_from(accounts)?,
+        })
+    }
+}
+
+/// Executes an order the owner signed off-chain, without an on-chain `Initialize`.
+///
+/// The previous instruction must be an Ed25519 precompile instruction verifying the
+/// owner's signature over the exact `Order` bytes. Sell tokens are moved by the owner's
+/// authority PDA, so the owner approves it once as delegate of the sell token account.
+/// Without an order account to track progress, signed orders are always filled in full,
+/// and their route never runs with the authority PDA's signature.
+pub fn process_execute_signed_order(accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
+    let ExecuteSignedOrderContext {
+        instructions_sysvar,
+        execute: context,
+    } = ExecuteSignedOrderContext::try_from(accounts)?;
+
+    let config = Config::load(context.config)?;
+    config.check_not_paused()?;
+
+    // 1. Parse arguments (authority bump + Order + route data)
+    if args.len() < 1 + Order::LEN {
+        return Err(SolverError::InvalidInstructionData.into());
+    }
+
+    let authority_bump = args[0];
+    let order_data = &args[1..1 + Order::LEN];
+
+    verify_ed25519_instruction(instructions_sysvar, context.owner.key(), order_data)?;
+
+    let order = Order::unpack_signed(
+        order_data,
+        context.owner.key(),
+        context.order_account.key(),
+        authority_bump,
+    )?;
+
+    // Nothing was escrowed for an order that never went on-chain
+    if order.mode()? != OrderMode::Delegate {
+        return Err(SolverError::InvalidOrderMode.into());
+    }
+
+    // The authority PDA is shared by all of the owner's approvals, lending it to the
+    // route would let a hop drain any other account the owner delegated to it
+    if order.sign_route != 0 {
+        return Err(SolverError::SignRouteNotAllowed.into());
+    }
+
+    if !order.validate_order_accounts(
+        context.from_token_account.key(),
+        context.to_token_account.key(),
+        context.referral_token_account.key(),
+    ) {
+        return Err(SolverError::InvalidOrderAccounts.into());
+    }
+
+    let seeds = Order::authority_signer_seeds(context.owner.key(), &authority_bump);
+
+    fill_order(
+        &context,
+        &config,
+        &order,
+        &seeds,
+        0,
+        order.sell_amount,
+        &args[1 + Order::LEN..],
+    )
+}
@@ -0,0 +1,143 @@
+use crate::error::SolverError;
+use crate::state::{config::Config, order::MAX_BPS};
+use crate::utils::accounts::{check_program, check_signer, check_writable};
+use crate::utils::{load_acc_mut_unchecked, DataLen, Unpackable};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::pubkey::{try_find_program_address, Pubkey};
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_system::instructions::CreateAccount;
+
+/// Settings written by `InitializeConfig` and `UpdateConfig`.
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct ConfigArgs {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u64,
+}
+
+impl ConfigArgs {
+    pub fn write_to(&self, config: &mut Config) -> ProgramResult {
+        if self.protocol_fee_bps > MAX_BPS {
+            return Err(SolverError::InvalidProtocolFee.into());
+        }
+
+        config.admin = self.admin;
+        config.treasury = self.treasury;
+        config.protocol_fee_bps = self.protocol_fee_bps;
+        Ok(())
+    }
+}
+
+/// Loader of upgradeable programs `BPFLoaderUpgradeab1e11111111111111111111111`.
+pub const BPF_LOADER_UPGRADEABLE_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+/// `UpgradeableLoaderState::ProgramData` tag, followed by the deploy slot and the
+/// optional upgrade authority.
+const PROGRAM_DATA_TAG: u32 = 3;
+
+/// Only the program's upgrade authority may initialize the config, so nobody can claim
+/// the admin role between the deploy and the initialization.
+pub struct InitializeConfigContext<'a> {
+    /// Upgrade authority of the program, paying for the config account.
+    pub payer: &'a AccountInfo,
+    pub config_account: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    /// ProgramData account of this program, holding its upgrade authority.
+    pub program_data: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for InitializeConfigContext<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [payer, config_account, system_program, program_data] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        check_signer(payer)?;
+        check_writable(payer, SolverError::AccountMustBeMut)?;
+        check_writable(config_account, SolverError::AccountMustBeMut)?;
+        check_program(system_program, &pinocchio_system::ID)?;
+
+        Ok(Self {
+            payer,
+            config_account,
+            system_program,
+            program_data,
+        })
+    }
+}
+
+/// Checks that `authority` is the upgrade authority recorded in this program's
+/// ProgramData account.
+fn check_upgrade_authority(program_data: &AccountInfo, authority: &Pubkey) -> ProgramResult {
+    let (program_data_key, _) =
+        try_find_program_address(&[crate::ID.as_ref()], &BPF_LOADER_UPGRADEABLE_ID)
+            .ok_or(SolverError::InvalidProgramData)?;
+    if program_data.key() != &program_data_key
+        || !program_data.is_owned_by(&BPF_LOADER_UPGRADEABLE_ID)
+    {
+        return Err(SolverError::InvalidProgramData.into());
+    }
+
+    let data = program_data.try_borrow_data()?;
+    let (Some(tag), Some(&has_authority), Some(upgrade_authority)) =
+        (data.get(0..4), data.get(12), data.get(13..45))
+    else {
+        return Err(SolverError::InvalidProgramData.into());
+    };
+    if tag != PROGRAM_DATA_TAG.to_le_bytes() {
+        return Err(SolverError::InvalidProgramData.into());
+    }
+
+    // An immutable program has no authority left to initialize the config
+    if has_authority != 1 || upgrade_authority != authority.as_ref() {
+        return Err(SolverError::InvalidAdmin.into());
+    }
+    Ok(())
+}
+
+pub fn process_initialize_config(accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
+    let context = InitializeConfigContext::try_from(accounts)?;
+    check_upgrade_authority(context.program_data, context.payer.key())?;
+    if args.len() != 1 + ConfigArgs::LEN {
+        // 1 byte config_bump + ConfigArgs data
+        return Err(SolverError::InvalidInstructionData.into());
+    }
+    let config_bump = args[0];
+    let config_args = ConfigArgs::unpack(&args[1..])?;
+
+    // Only the canonical bump is accepted, so a single config address exists
+    let (_, canonical_bump) = Config::find_address(context.config_account)?;
+    if config_bump != canonical_bump {
+        return Err(SolverError::InvalidConfigAccount.into());
+    }
+
+    // The config is a singleton, creating it a second time fails in the system program
+    let seeds = [
+        Seed::from(Config::SEED),
+        Seed::from(core::slice::from_ref(&config_bump)),
+    ];
+    let signer = Signer::from(&seeds);
+
+    CreateAccount {
+        from: context.payer,
+        to: context.config_account,
+        lamports: Rent::get()?.minimum_balance(Config::LEN),
+        space: Config::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&[signer])?;
+
+    let mut data = context.config_account.try_borrow_mut_data()?;
+    let config = unsafe { load_acc_mut_unchecked::<Config>(&mut data)? };
+    config_args.write_to(config)?;
+    config.is_initialized = 1;
+    config.bump = config_bump;
+
+    Ok(())
+}
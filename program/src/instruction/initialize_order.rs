@@ -1,56 +1,195 @@
+use crate::state::config::Config;
+use crate::utils::accounts::{check_distinct, check_program, check_signer, check_writable};
+use crate::utils::token::{
+    check_token_program, load_mint, Approve, CreateAssociatedTokenAccountIdempotent, SyncNative,
+    TransferChecked, TransferFee, ASSOCIATED_TOKEN_PROGRAM_ID,
+};
 use crate::utils::DataLen;
-use crate::{error::SolverError, state::order::Order};
-use light_hasher::{Hasher, Keccak};
-use pinocchio::pubkey::create_program_address;
+use crate::{
+    error::SolverError,
+    state::order::{Order, OrderAccount, OrderMode},
+};
+use pinocchio::instruction::Signer;
+use pinocchio::sysvars::{clock::Clock, rent::Rent, Sysvar};
 use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_system::instructions::{CreateAccount, Transfer as SystemTransfer};
 
 pub struct InitializeOrderContext<'a> {
     pub owner: &'a AccountInfo,
     pub order_account: &'a AccountInfo,
+    pub rent_payer: &'a AccountInfo,
+    pub from_token_account: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub sell_mint: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+    pub associated_token_program: &'a AccountInfo,
+    pub config: &'a AccountInfo,
+    pub to_token_account: &'a AccountInfo,
+    pub referral_token_account: &'a AccountInfo,
+    /// Extra accounts of the sell mint's transfer hook, if any.
+    pub hook_accounts: &'a [AccountInfo],
 }
 
 impl<'a> TryFrom<&'a [AccountInfo]> for InitializeOrderContext<'a> {
     type Error = ProgramError;
 
     fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
-        let [owner, order_account] = accounts else {
+        let [owner, order_account, rent_payer, from_token_account, vault, sell_mint, system_program, token_program, associated_token_program, config, to_token_account, referral_token_account, hook_accounts @ ..] =
+            accounts
+        else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
-        if !owner.is_signer() {
-            return Err(ProgramError::MissingRequiredSignature);
+        check_signer(owner)?;
+
+        if !rent_payer.is_signer() {
+            return Err(SolverError::InvalidRentPayer.into());
         }
+        check_writable(rent_payer, SolverError::AccountMustBeMut)?;
+
+        check_writable(order_account, SolverError::OrderAccountMustBeMut)?;
+        check_writable(from_token_account, SolverError::AccountMustBeMut)?;
+        check_program(system_program, &pinocchio_system::ID)?;
+        check_token_program(token_program)?;
+        check_program(associated_token_program, &ASSOCIATED_TOKEN_PROGRAM_ID)?;
+        check_distinct(&[order_account, from_token_account, vault, to_token_account])?;
 
         Ok(Self {
             owner,
             order_account,
+            rent_payer,
+            from_token_account,
+            vault,
+            sell_mint,
+            system_program,
+            token_program,
+            associated_token_program,
+            config,
+            to_token_account,
+            referral_token_account,
+            hook_accounts,
         })
     }
 }
 
 pub fn process_initialize_order(accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
     let context = InitializeOrderContext::try_from(accounts)?;
+    Config::load(context.config)?.check_not_paused()?;
+
     if args.len() != 1 + Order::LEN {
-        // 1 byte order_bump + 8 bytes order_nonce + Order data
+        // 1 byte order_bump + Order data
         return Err(SolverError::InvalidInstructionData.into());
     }
-    let order_bump = &args[0];
+    let order_bump = args[0];
     let intent_body = &args[1..];
-    let intent_hash = Keccak::hashv(&[intent_body]).unwrap();
-
-    let calculated_order_pubkey = create_program_address(
-        &[
-            b"order",
-            context.owner.key().as_ref(),
-            intent_hash.as_ref(),
-            &[*order_bump],
-        ],
-        &crate::ID,
-    )
-    .unwrap();
-
-    if &calculated_order_pubkey != context.order_account.key() {
-        return Err(SolverError::InvalidOrderAccount.into());
+
+    let (order, intent_hash) = Order::validate_and_unpack(
+        intent_body,
+        context.owner.key(),
+        context.order_account.key(),
+        order_bump,
+    )?;
+
+    if !order.validate_order_accounts(
+        context.from_token_account.key(),
+        context.to_token_account.key(),
+        context.referral_token_account.key(),
+    ) {
+        return Err(SolverError::InvalidOrderAccounts.into());
+    }
+
+    // Reject orders that could never be priced before locking any funds
+    order.check_price_parameters()?;
+
+    order.check_native_accounts(context.owner.key())?;
+    order.check_token_accounts(
+        context.owner.key(),
+        context.sell_mint,
+        context.from_token_account,
+        context.to_token_account,
+        context.referral_token_account,
+    )?;
+
+    let native_sell = order.native_sell != 0;
+
+    // 1. Create the order PDA, funded by the rent payer
+    let seeds = Order::signer_seeds(context.owner.key(), &intent_hash, &order_bump);
+    let signer = Signer::from(&seeds);
+
+    CreateAccount {
+        from: context.rent_payer,
+        to: context.order_account,
+        lamports: Rent::get()?.minimum_balance(OrderAccount::LEN),
+        space: OrderAccount::LEN as u64,
+        owner: &crate::ID,
+    }
+    .invoke_signed(&[signer])?;
+
+    // Publish the intent so solvers can discover it on-chain, nothing is filled yet
+    context.order_account.try_borrow_mut_data()?[..Order::LEN].copy_from_slice(intent_body);
+
+    // 2. Give the order PDA authority over the sell tokens
+    match order.mode()? {
+        OrderMode::Escrow => {
+            // Escrow the sell tokens into the vault owned by the order PDA
+            CreateAssociatedTokenAccountIdempotent {
+                funding_account: context.rent_payer,
+                account: context.vault,
+                wallet: context.order_account,
+                mint: context.sell_mint,
+                system_program: context.system_program,
+                token_program: context.token_program,
+            }
+            .invoke()?;
+
+            if native_sell {
+                // Wrap the owner's lamports straight into the vault
+                SystemTransfer {
+                    from: context.owner,
+                    to: context.vault,
+                    lamports: order.sell_amount,
+                }
+                .invoke()?;
+
+                SyncNative {
+                    native_token: context.vault,
+                    token_program: context.token_program,
+                }
+                .invoke()?;
+
+                return Ok(());
+            }
+
+            // Cover the sell mint's transfer fee so the vault holds exactly `sell_amount`
+            let amount = match TransferFee::load(context.sell_mint, Clock::get()?.epoch)? {
+                Some(transfer_fee) => transfer_fee.gross_amount(order.sell_amount)?,
+                None => order.sell_amount,
+            };
+
+            TransferChecked {
+                from: context.from_token_account,
+                mint: context.sell_mint,
+                to: context.vault,
+                authority: context.owner,
+                amount,
+                decimals: load_mint(context.sell_mint)?.decimals(),
+                token_program: context.token_program,
+                hook_accounts: context.hook_accounts,
+            }
+            .invoke_signed(&[])?;
+        }
+        OrderMode::Delegate => {
+            // Keep the sell tokens with the owner, delegating exactly `sell_amount`
+            Approve {
+                source: context.from_token_account,
+                delegate: context.order_account,
+                authority: context.owner,
+                amount: order.sell_amount,
+                token_program: context.token_program,
+            }
+            .invoke()?;
+        }
     }
 
     Ok(())
@@ -2,13 +2,26 @@ use pinocchio::program_error::ProgramError;
 use pinocchio::pubkey::Pubkey;
 use pinocchio::{account_info::AccountInfo, ProgramResult};
 
+pub mod cancel_order;
 pub mod execute_order;
+pub mod execute_signed_order;
+pub mod initialize_config;
 pub mod initialize_order;
+pub mod route;
+pub mod set_paused;
+pub mod update_allow_list;
+pub mod update_config;
 
 #[repr(u8)]
 pub enum Instruction {
     Initialize,
+    Cancel,
     Execute,
+    InitializeConfig,
+    UpdateConfig,
+    SetPaused,
+    ExecuteSigned,
+    UpdateAllowList,
 }
 
 impl TryFrom<u8> for Instruction {
@@ -17,7 +30,13 @@ impl TryFrom<u8> for Instruction {
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(Instruction::Initialize),
+            1 => Ok(Instruction::Cancel),
             2 => Ok(Instruction::Execute),
+            3 => Ok(Instruction::InitializeConfig),
+            4 => Ok(Instruction::UpdateConfig),
+            5 => Ok(Instruction::SetPaused),
+            6 => Ok(Instruction::ExecuteSigned),
+            7 => Ok(Instruction::UpdateAllowList),
             _ => Err(ProgramError::InvalidInstructionData),
         }
     }
@@ -34,6 +53,18 @@ pub fn process_instruction(
 
     match Instruction::try_from(*discriminator)? {
         Instruction::Initialize => initialize_order::process_initialize_order(accounts, args),
+        Instruction::Cancel => cancel_order::process_cancel_order(accounts, args),
         Instruction::Execute => execute_order::process_execute_order(accounts, args),
+        Instruction::InitializeConfig => {
+            initialize_config::process_initialize_config(accounts, args)
+        }
+        Instruction::UpdateConfig => update_config::process_update_config(accounts, args),
+        Instruction::SetPaused => set_paused::process_set_paused(accounts, args),
+        Instruction::ExecuteSigned => {
+            execute_signed_order::process_execute_signed_order(accounts, args)
+        }
+        Instruction::UpdateAllowList => {
+            update_allow_list::process_update_allow_list(accounts, args)
+        }
     }
 }
@@ -0,0 +1,95 @@
+use crate::error::SolverError;
+use alloc::vec::Vec;
+use core::ops::Range;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError};
+
+/// Size of a hop header: program index, accounts start, accounts length and data length.
+const HOP_HEADER_LEN: usize = 5;
+
+/// One CPI of a solver route. `program_index` and `accounts` index the remaining accounts
+/// of the execute instruction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouteHop<'a> {
+    pub program_index: usize,
+    pub accounts: Range<usize>,
+    pub data: &'a [u8],
+}
+
+impl RouteHop<'_> {
+    /// Looks up the program and accounts of the hop in `remaining_accounts`.
+    pub fn resolve<'b>(
+        &self,
+        remaining_accounts: &'b [AccountInfo],
+    ) -> Result<(&'b AccountInfo, &'b [AccountInfo]), ProgramError> {
+        let program = remaining_accounts
+            .get(self.program_index)
+            .ok_or(SolverError::InvalidRoute)?;
+        let accounts = remaining_accounts
+            .get(self.accounts.clone())
+            .ok_or(SolverError::InvalidRoute)?;
+        Ok((program, accounts))
+    }
+}
+
+/// A solver route: the transfer hook accounts of the sell mint, the first
+/// `hook_accounts` remaining accounts, and the hops invoked in order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route<'a> {
+    pub hook_accounts: usize,
+    pub hops: Vec<RouteHop<'a>>,
+}
+
+impl Route<'_> {
+    /// The transfer hook accounts at the start of `remaining_accounts`.
+    pub fn hook_accounts<'b>(
+        &self,
+        remaining_accounts: &'b [AccountInfo],
+    ) -> Result<&'b [AccountInfo], ProgramError> {
+        remaining_accounts
+            .get(..self.hook_accounts)
+            .ok_or(SolverError::InvalidRoute.into())
+    }
+}
+
+/// Parses route data: `[hook_accounts: u8, hop_count: u8]` followed by, per hop,
+/// `[program_index: u8, accounts_start: u8, accounts_len: u8, data_len: u16 LE, data]`.
+pub fn parse_route(data: &[u8]) -> Result<Route<'_>, ProgramError> {
+    let [hook_accounts, hop_count, hops_data @ ..] = data else {
+        return Err(SolverError::InvalidRoute.into());
+    };
+    let (hop_count, mut data) = (*hop_count, hops_data);
+    if hop_count == 0 {
+        return Err(SolverError::InvalidRoute.into());
+    }
+
+    let mut hops = Vec::with_capacity(hop_count as usize);
+    for _ in 0..hop_count {
+        if data.len() < HOP_HEADER_LEN {
+            return Err(SolverError::InvalidRoute.into());
+        }
+        let (header, rest) = data.split_at(HOP_HEADER_LEN);
+        let accounts_start = header[1] as usize;
+        let data_len = u16::from_le_bytes([header[3], header[4]]) as usize;
+        if rest.len() < data_len {
+            return Err(SolverError::InvalidRoute.into());
+        }
+        let (hop_data, rest) = rest.split_at(data_len);
+
+        hops.push(RouteHop {
+            program_index: header[0] as usize,
+            accounts: accounts_start..accounts_start + header[2] as usize,
+            data: hop_data,
+        });
+        data = rest;
+    }
+
+    // Trailing bytes mean the route was not encoded the way the solver intended
+    if !data.is_empty() {
+        return Err(SolverError::InvalidRoute.into());
+    }
+
+    Ok(Route {
+        hook_accounts: *hook_accounts as usize,
+        hops,
+    })
+}
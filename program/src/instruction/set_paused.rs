@@ -0,0 +1,51 @@
+use crate::error::SolverError;
+use crate::state::config::Config;
+use crate::utils::accounts::{check_signer, check_writable};
+use crate::utils::load_acc_mut;
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+pub struct SetPausedContext<'a> {
+    pub admin: &'a AccountInfo,
+    pub config_account: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for SetPausedContext<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, config_account] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        check_signer(admin)?;
+        check_writable(config_account, SolverError::AccountMustBeMut)?;
+
+        Ok(Self {
+            admin,
+            config_account,
+        })
+    }
+}
+
+/// Halts (`1`) or resumes (`0`) order initialization and execution. Cancels stay
+/// available so users can always exit.
+pub fn process_set_paused(accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
+    let context = SetPausedContext::try_from(accounts)?;
+    let [is_paused] = args else {
+        return Err(SolverError::InvalidInstructionData.into());
+    };
+    if *is_paused > 1 {
+        return Err(SolverError::InvalidInstructionData.into());
+    }
+
+    let config = Config::load(context.config_account)?;
+    if &config.admin != context.admin.key() {
+        return Err(SolverError::InvalidAdmin.into());
+    }
+
+    let mut data = context.config_account.try_borrow_mut_data()?;
+    let config = unsafe { load_acc_mut::<Config>(&mut data)? };
+    config.is_paused = *is_paused;
+
+    Ok(())
+}
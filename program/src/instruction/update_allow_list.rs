@@ -0,0 +1,102 @@
+use crate::error::SolverError;
+use crate::state::allow_list::{AllowList, MAX_ALLOWED_PROGRAMS};
+use crate::state::config::Config;
+use crate::utils::accounts::{check_program, check_signer, check_writable};
+use crate::utils::{load_acc_mut_unchecked, DataLen};
+use pinocchio::instruction::{Seed, Signer};
+use pinocchio::pubkey::{Pubkey, PUBKEY_BYTES};
+use pinocchio::sysvars::{rent::Rent, Sysvar};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+use pinocchio_system::instructions::CreateAccount;
+
+pub struct UpdateAllowListContext<'a> {
+    pub admin: &'a AccountInfo,
+    pub config_account: &'a AccountInfo,
+    pub allow_list_account: &'a AccountInfo,
+    pub system_program: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateAllowListContext<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, config_account, allow_list_account, system_program] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        check_signer(admin)?;
+        check_writable(admin, SolverError::AccountMustBeMut)?;
+        check_writable(allow_list_account, SolverError::AccountMustBeMut)?;
+        check_program(system_program, &pinocchio_system::ID)?;
+
+        Ok(Self {
+            admin,
+            config_account,
+            allow_list_account,
+            system_program,
+        })
+    }
+}
+
+/// Replaces the programs solver routes may invoke, creating the allow-list on first use
+/// with the admin as payer.
+pub fn process_update_allow_list(accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
+    let context = UpdateAllowListContext::try_from(accounts)?;
+
+    // 1 byte allow_list_bump + up to MAX_ALLOWED_PROGRAMS program ids
+    let Some((&allow_list_bump, programs)) = args.split_first() else {
+        return Err(SolverError::InvalidInstructionData.into());
+    };
+    if programs.len() % PUBKEY_BYTES != 0 || programs.len() / PUBKEY_BYTES > MAX_ALLOWED_PROGRAMS {
+        return Err(SolverError::InvalidInstructionData.into());
+    }
+
+    let config = Config::load(context.config_account)?;
+    if &config.admin != context.admin.key() {
+        return Err(SolverError::InvalidAdmin.into());
+    }
+
+    if !context.allow_list_account.is_owned_by(&crate::ID) {
+        // Only the canonical bump is accepted, so a single allow-list address exists
+        let (_, canonical_bump) = AllowList::find_address(context.allow_list_account)?;
+        if allow_list_bump != canonical_bump {
+            return Err(SolverError::InvalidAllowListAccount.into());
+        }
+
+        let seeds = [
+            Seed::from(AllowList::SEED),
+            Seed::from(core::slice::from_ref(&allow_list_bump)),
+        ];
+
+        CreateAccount {
+            from: context.admin,
+            to: context.allow_list_account,
+            lamports: Rent::get()?.minimum_balance(AllowList::LEN),
+            space: AllowList::LEN as u64,
+            owner: &crate::ID,
+        }
+        .invoke_signed(&[Signer::from(&seeds)])?;
+
+        let mut data = context.allow_list_account.try_borrow_mut_data()?;
+        let allow_list = unsafe { load_acc_mut_unchecked::<AllowList>(&mut data)? };
+        allow_list.is_initialized = 1;
+        allow_list.bump = allow_list_bump;
+    } else {
+        // Checks the address of an existing allow-list
+        AllowList::load(context.allow_list_account)?;
+    }
+
+    let mut data = context.allow_list_account.try_borrow_mut_data()?;
+    let allow_list = unsafe { load_acc_mut_unchecked::<AllowList>(&mut data)? };
+    allow_list.programs = [Pubkey::default(); MAX_ALLOWED_PROGRAMS];
+    for (slot, program) in allow_list
+        .programs
+        .iter_mut()
+        .zip(programs.chunks_exact(PUBKEY_BYTES))
+    {
+        slot.copy_from_slice(program);
+    }
+    allow_list.count = (programs.len() / PUBKEY_BYTES) as u8;
+
+    Ok(())
+}
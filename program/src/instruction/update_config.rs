@@ -0,0 +1,46 @@
+use crate::error::SolverError;
+use crate::instruction::initialize_config::ConfigArgs;
+use crate::state::config::Config;
+use crate::utils::accounts::{check_signer, check_writable};
+use crate::utils::{load_acc_mut, DataLen, Unpackable};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
+
+pub struct UpdateConfigContext<'a> {
+    pub admin: &'a AccountInfo,
+    pub config_account: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for UpdateConfigContext<'a> {
+    type Error = ProgramError;
+
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let [admin, config_account] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        check_signer(admin)?;
+        check_writable(config_account, SolverError::AccountMustBeMut)?;
+
+        Ok(Self {
+            admin,
+            config_account,
+        })
+    }
+}
+
+pub fn process_update_config(accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
+    let context = UpdateConfigContext::try_from(accounts)?;
+    if args.len() != ConfigArgs::LEN {
+        return Err(SolverError::InvalidInstructionData.into());
+    }
+    let config_args = ConfigArgs::unpack(args)?;
+
+    let config = Config::load(context.config_account)?;
+    if &config.admin != context.admin.key() {
+        return Err(SolverError::InvalidAdmin.into());
+    }
+
+    let mut data = context.config_account.try_borrow_mut_data()?;
+    let config = unsafe { load_acc_mut::<Config>(&mut data)? };
+    config_args.write_to(config)
+}
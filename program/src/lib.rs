@@ -1,7 +1,7 @@
 #![no_std]
 extern crate alloc;
 pub mod entrypoint;
-mod error;
+pub mod error;
 pub mod instruction;
 pub mod state;
 mod utils;
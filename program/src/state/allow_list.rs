@@ -0,0 +1,75 @@
+use crate::{
+    error::SolverError,
+    utils::{load_acc, Initialized},
+};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::{try_find_program_address, Pubkey},
+    ProgramResult,
+};
+
+/// Maximum number of programs the allow-list holds.
+pub const MAX_ALLOWED_PROGRAMS: usize = 16;
+
+/// Programs a solver route may invoke with the order's signer authority, stored in a
+/// singleton PDA derived from `[b"allow_list"]` and managed by the config admin.
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct AllowList {
+    pub programs: [Pubkey; MAX_ALLOWED_PROGRAMS],
+    pub count: u8,
+    pub is_initialized: u8,
+    pub bump: u8,
+    pub _padding: [u8; 5],
+}
+
+impl Initialized for AllowList {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized == 1
+    }
+}
+
+impl AllowList {
+    pub const SEED: &'static [u8] = b"allow_list";
+
+    /// Canonical allow-list address and bump, failing unless `account` is that address.
+    pub fn find_address(account: &AccountInfo) -> Result<(Pubkey, u8), ProgramError> {
+        let (allow_list_key, bump) = try_find_program_address(&[Self::SEED], &crate::ID)
+            .ok_or(SolverError::InvalidAllowListAccount)?;
+        if &allow_list_key != account.key() {
+            return Err(SolverError::InvalidAllowListAccount.into());
+        }
+        Ok((allow_list_key, bump))
+    }
+
+    /// Reads the allow-list from its PDA, checking owner, address and initialization.
+    pub fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(SolverError::InvalidAllowListAccount.into());
+        }
+
+        let data = account.try_borrow_data()?;
+        let allow_list = *unsafe { load_acc::<AllowList>(&data) }
+            .map_err(|_| ProgramError::from(SolverError::InvalidAllowListAccount))?;
+
+        let (_, bump) = Self::find_address(account)?;
+        if allow_list.bump != bump {
+            return Err(SolverError::InvalidAllowListAccount.into());
+        }
+
+        Ok(allow_list)
+    }
+
+    pub fn programs(&self) -> &[Pubkey] {
+        &self.programs[..(self.count as usize).min(MAX_ALLOWED_PROGRAMS)]
+    }
+
+    pub fn check_allowed(&self, program: &Pubkey) -> ProgramResult {
+        if !self.programs().contains(program) {
+            return Err(SolverError::ProgramNotAllowed.into());
+        }
+        Ok(())
+    }
+}
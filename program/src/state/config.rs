@@ -0,0 +1,85 @@
+use crate::{
+    error::SolverError,
+    state::order::MAX_BPS,
+    utils::{load_acc, Initialized},
+};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    program_error::ProgramError,
+    pubkey::{try_find_program_address, Pubkey},
+    ProgramResult,
+};
+
+/// Program-wide settings, stored in a singleton PDA derived from `[b"config"]`.
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct Config {
+    pub admin: Pubkey,
+    /// Owner every treasury token account must have, one account per sell mint.
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u64,
+    pub is_initialized: u8,
+    pub is_paused: u8,
+    pub bump: u8,
+    pub _padding: [u8; 5],
+}
+
+impl Initialized for Config {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized == 1
+    }
+}
+
+impl Config {
+    pub const SEED: &'static [u8] = b"config";
+
+    /// Canonical config address and bump, failing unless `account` is that address.
+    pub fn find_address(account: &AccountInfo) -> Result<(Pubkey, u8), ProgramError> {
+        let (config_key, bump) = try_find_program_address(&[Self::SEED], &crate::ID)
+            .ok_or(SolverError::InvalidConfigAccount)?;
+        if &config_key != account.key() {
+            return Err(SolverError::InvalidConfigAccount.into());
+        }
+        Ok((config_key, bump))
+    }
+
+    /// Reads the config from its PDA, checking owner, address and initialization.
+    pub fn load(account: &AccountInfo) -> Result<Self, ProgramError> {
+        if !account.is_owned_by(&crate::ID) {
+            return Err(SolverError::InvalidConfigAccount.into());
+        }
+
+        let data = account.try_borrow_data()?;
+        let config = *unsafe { load_acc::<Config>(&data) }
+            .map_err(|_| ProgramError::from(SolverError::InvalidConfigAccount))?;
+
+        let (_, bump) = Self::find_address(account)?;
+        if config.bump != bump {
+            return Err(SolverError::InvalidConfigAccount.into());
+        }
+
+        Ok(config)
+    }
+
+    pub fn check_not_paused(&self) -> ProgramResult {
+        if self.is_paused != 0 {
+            return Err(SolverError::ProgramPaused.into());
+        }
+        Ok(())
+    }
+
+    /// Protocol fee owed on a fill of `fill_amount` sell tokens.
+    pub fn protocol_fee_amount(&self, fill_amount: u64) -> Result<u64, ProgramError> {
+        if self.protocol_fee_bps > MAX_BPS {
+            return Err(SolverError::InvalidProtocolFee.into());
+        }
+
+        let fee = (fill_amount as u128)
+            .checked_mul(self.protocol_fee_bps as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / MAX_BPS as u128;
+
+        Ok(fee as u64)
+    }
+}
@@ -0,0 +1,85 @@
+use crate::error::SolverError;
+use bytemuck::{Pod, Zeroable};
+use pinocchio::program_error::ProgramError;
+
+/// Maximum number of breakpoints of a piecewise-linear curve.
+pub const MAX_BREAKPOINTS: usize = 4;
+
+/// Shape of the Dutch auction taking the required buy amount from `buy_amount` down to
+/// `minimun_buy_amount`.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuctionCurve {
+    /// Linear interpolation over `[start_time, deadline]`.
+    Linear,
+    /// A fixed `amount_decrease_per_second` from `start_time`, floored at the minimum.
+    PerSecond,
+    /// The distance to the minimum halves every `curve_period` seconds.
+    Exponential,
+    /// Linear interpolation sampled at the start of every `curve_period` seconds step.
+    Stepwise,
+    /// Linear between the order's breakpoints, from `buy_amount` at `start_time` to
+    /// `minimun_buy_amount` at `deadline`.
+    PiecewiseLinear,
+}
+
+impl TryFrom<u8> for AuctionCurve {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AuctionCurve::Linear),
+            1 => Ok(AuctionCurve::PerSecond),
+            2 => Ok(AuctionCurve::Exponential),
+            3 => Ok(AuctionCurve::Stepwise),
+            4 => Ok(AuctionCurve::PiecewiseLinear),
+            _ => Err(SolverError::InvalidAuctionCurve.into()),
+        }
+    }
+}
+
+/// A point of a piecewise-linear curve: the required buy amount `time_offset` seconds
+/// after `start_time`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct Breakpoint {
+    pub time_offset: u64,
+    pub buy_amount: u64,
+}
+
+/// `2^(-1 / 2^k)` for `k` in `1..=16`, in Q32 fixed point.
+const HALVING_ROOTS: [u64; 16] = [
+    3037000500, 3611622603, 3938502376, 4112874773, 4202935003, 4248701965, 4271771996, 4283353945,
+    4289156690, 4292061010, 4293513907, 4294240540, 4294603903, 4294785595, 4294876445, 4294921870,
+];
+
+/// Amount between two points at `x`, with `from.time_offset <= x < to.time_offset` and a
+/// non-increasing amount.
+pub fn interpolate(from: Breakpoint, to: Breakpoint, x: u64) -> u64 {
+    let range = (from.buy_amount - to.buy_amount) as u128;
+    let elapsed = (x - from.time_offset) as u128;
+    let duration = (to.time_offset - from.time_offset) as u128;
+
+    // The reduction is at most `range`, so it fits back into u64
+    from.buy_amount - (range * elapsed / duration) as u64
+}
+
+/// `range * 2^(-elapsed / half_life)`, with the fractional half-life resolved to 16 bits.
+pub fn exponential_decay(range: u64, elapsed: u64, half_life: u64) -> u64 {
+    let halvings = elapsed / half_life;
+    if halvings >= u64::BITS as u64 {
+        return 0;
+    }
+
+    // Fraction of the current half-life in Q16
+    let fraction = (((elapsed % half_life) as u128) << 16) / half_life as u128;
+
+    let mut factor: u128 = 1 << 32;
+    for (bit, root) in HALVING_ROOTS.iter().enumerate() {
+        if fraction & (1 << (15 - bit)) != 0 {
+            factor = (factor * *root as u128) >> 32;
+        }
+    }
+
+    (((range as u128 * factor) >> 32) >> halvings) as u64
+}
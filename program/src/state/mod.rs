@@ -1 +1,6 @@
+pub mod allow_list;
+pub mod config;
+pub mod curve;
+pub mod nonce;
+pub mod oracle;
 pub mod order;
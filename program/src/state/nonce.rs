@@ -0,0 +1,121 @@
+use crate::{
+    error::SolverError,
+    utils::{load_acc, load_acc_mut, load_acc_mut_unchecked, DataLen, Initialized},
+};
+use bytemuck::{Pod, Zeroable};
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{Seed, Signer},
+    pubkey::{try_find_program_address, Pubkey},
+    sysvars::{rent::Rent, Sysvar},
+    ProgramResult,
+};
+use pinocchio_system::instructions::{Allocate, Assign, Transfer};
+
+/// Nonces of one owner that were already filled, stored in a PDA derived from
+/// `[b"nonces", owner, word]` where `word = nonce / NonceBitmap::NONCES_PER_ACCOUNT`.
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct NonceBitmap {
+    pub bits: [u8; 32],
+    pub is_initialized: u8,
+    pub bump: u8,
+    pub _padding: [u8; 6],
+}
+
+impl Initialized for NonceBitmap {
+    fn is_initialized(&self) -> bool {
+        self.is_initialized == 1
+    }
+}
+
+impl NonceBitmap {
+    pub const SEED: &'static [u8] = b"nonces";
+
+    pub const NONCES_PER_ACCOUNT: u64 = 256;
+
+    /// Index of the bitmap account covering `nonce`.
+    pub fn word(nonce: u64) -> u64 {
+        nonce / Self::NONCES_PER_ACCOUNT
+    }
+
+    pub fn is_used(&self, nonce: u64) -> bool {
+        let bit = nonce % Self::NONCES_PER_ACCOUNT;
+        self.bits[(bit / 8) as usize] & (1 << (bit % 8)) != 0
+    }
+
+    pub fn mark_used(&mut self, nonce: u64) {
+        let bit = nonce % Self::NONCES_PER_ACCOUNT;
+        self.bits[(bit / 8) as usize] |= 1 << (bit % 8);
+    }
+
+    /// Fails with `OrderAlreadyFilled` if `nonce` of `owner` was used, creating its bitmap
+    /// account funded by `payer` on first use.
+    pub fn check_unused(
+        account: &AccountInfo,
+        owner: &Pubkey,
+        payer: &AccountInfo,
+        nonce: u64,
+    ) -> ProgramResult {
+        let word = Self::word(nonce).to_le_bytes();
+        let (nonce_key, bump) =
+            try_find_program_address(&[Self::SEED, owner.as_ref(), &word], &crate::ID)
+                .ok_or(SolverError::InvalidNonceAccount)?;
+        if &nonce_key != account.key() {
+            return Err(SolverError::InvalidNonceAccount.into());
+        }
+
+        if !account.is_owned_by(&crate::ID) {
+            let seeds = [
+                Seed::from(Self::SEED),
+                Seed::from(owner),
+                Seed::from(word.as_slice()),
+                Seed::from(core::slice::from_ref(&bump)),
+            ];
+
+            // Anyone can send lamports to the address ahead of time, which would make
+            // CreateAccount fail, so only the missing rent is topped up
+            let lamports = Rent::get()?
+                .minimum_balance(Self::LEN)
+                .saturating_sub(account.lamports());
+            if lamports > 0 {
+                Transfer {
+                    from: payer,
+                    to: account,
+                    lamports,
+                }
+                .invoke()?;
+            }
+            Allocate {
+                account,
+                space: Self::LEN as u64,
+            }
+            .invoke_signed(&[Signer::from(&seeds)])?;
+            Assign {
+                account,
+                owner: &crate::ID,
+            }
+            .invoke_signed(&[Signer::from(&seeds)])?;
+
+            let mut data = account.try_borrow_mut_data()?;
+            let bitmap = unsafe { load_acc_mut_unchecked::<NonceBitmap>(&mut data)? };
+            bitmap.is_initialized = 1;
+            bitmap.bump = bump;
+            return Ok(());
+        }
+
+        let data = account.try_borrow_data()?;
+        if unsafe { load_acc::<NonceBitmap>(&data)? }.is_used(nonce) {
+            return Err(SolverError::OrderAlreadyFilled.into());
+        }
+
+        Ok(())
+    }
+
+    /// Marks `nonce` as used in a bitmap account checked by [`NonceBitmap::check_unused`].
+    pub fn mark(account: &AccountInfo, nonce: u64) -> ProgramResult {
+        let mut data = account.try_borrow_mut_data()?;
+        unsafe { load_acc_mut::<NonceBitmap>(&mut data)? }.mark_used(nonce);
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+use crate::{error::SolverError, state::order::MAX_BPS};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey};
+
+/// Largest supported `OraclePrice::decimals`, so `10^decimals` fits in a u64.
+pub const MAX_ORACLE_DECIMALS: u8 = 19;
+
+/// Pyth receiver program `rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ`, owner of the
+/// `PriceUpdateV2` accounts read by the oracle guard.
+pub const PYTH_RECEIVER_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Anchor discriminator of `PriceUpdateV2`: `sha256("account:PriceUpdateV2")[..8]`.
+pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+/// `VerificationLevel::Full`, the only level trusted by the guard. It is one byte long,
+/// `Partial` carries its number of signatures.
+const VERIFICATION_LEVEL_FULL: u8 = 1;
+
+/// Offset of `PriceFeedMessage` in a fully verified `PriceUpdateV2`: discriminator,
+/// write authority and verification level.
+const PRICE_MESSAGE_OFFSET: usize = 8 + 32 + 1;
+
+/// Price read by the oracle guard from a Pyth `PriceUpdateV2` account: whole buy tokens per
+/// whole sell token, scaled by `10^decimals`. Owners pick a feed quoted in those units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OraclePrice {
+    pub price: u64,
+    /// Unix timestamp of the last price update.
+    pub publish_time: i64,
+    pub decimals: u8,
+}
+
+impl OraclePrice {
+    pub fn load(account: &AccountInfo, feed_id: &[u8; 32]) -> Result<Self, ProgramError> {
+        if !account.is_owned_by(&PYTH_RECEIVER_ID) {
+            return Err(SolverError::InvalidOracleAccount.into());
+        }
+        Self::parse_price_update(&account.try_borrow_data()?, feed_id)
+    }
+
+    /// Parses the price message of a fully verified `PriceUpdateV2` of `feed_id`:
+    /// `feed_id [32] | price i64 | conf u64 | exponent i32 | publish_time i64 | ..`.
+    pub fn parse_price_update(data: &[u8], feed_id: &[u8; 32]) -> Result<Self, ProgramError> {
+        let invalid = || ProgramError::from(SolverError::InvalidOracleAccount);
+
+        if data.get(..8) != Some(PRICE_UPDATE_V2_DISCRIMINATOR.as_slice())
+            || data.get(40) != Some(&VERIFICATION_LEVEL_FULL)
+        {
+            return Err(invalid());
+        }
+
+        let message_feed_id = data
+            .get(PRICE_MESSAGE_OFFSET..PRICE_MESSAGE_OFFSET + 32)
+            .ok_or_else(invalid)?;
+        if message_feed_id != feed_id.as_slice() {
+            return Err(SolverError::OracleFeedMismatch.into());
+        }
+
+        let message = data.get(PRICE_MESSAGE_OFFSET + 32..).ok_or_else(invalid)?;
+        let read = |range: core::ops::Range<usize>| message.get(range).ok_or_else(invalid);
+        let price = i64::from_le_bytes(read(0..8)?.try_into().map_err(|_| invalid())?);
+        let exponent = i32::from_le_bytes(read(16..20)?.try_into().map_err(|_| invalid())?);
+        let publish_time = i64::from_le_bytes(read(20..28)?.try_into().map_err(|_| invalid())?);
+
+        // A non-positive price or a positive exponent can't be a buy-per-sell ratio
+        let decimals = exponent
+            .checked_neg()
+            .and_then(|decimals| u8::try_from(decimals).ok())
+            .filter(|decimals| *decimals <= MAX_ORACLE_DECIMALS)
+            .ok_or_else(invalid)?;
+        if price <= 0 {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            price: price as u64,
+            publish_time,
+            decimals,
+        })
+    }
+
+    /// Checks that the price was published at most `max_staleness` seconds before
+    /// `current_time`.
+    pub fn check_fresh(&self, current_time: i64, max_staleness: u64) -> Result<(), ProgramError> {
+        let age = current_time.saturating_sub(self.publish_time);
+        if age < 0 || age as u64 > max_staleness {
+            return Err(SolverError::OraclePriceStale.into());
+        }
+        Ok(())
+    }
+
+    /// Checks that `buy_amount` received for `fill_amount` sell tokens is at most
+    /// `max_deviation_bps` below the oracle price. Amounts are in base units of mints with
+    /// `sell_decimals` and `buy_decimals`.
+    pub fn check_deviation(
+        &self,
+        fill_amount: u64,
+        buy_amount: u64,
+        max_deviation_bps: u64,
+        sell_decimals: u8,
+        buy_decimals: u8,
+    ) -> Result<(), ProgramError> {
+        if max_deviation_bps > MAX_BPS {
+            return Err(SolverError::InvalidOracleGuard.into());
+        }
+
+        // The price is quoted in whole tokens, so it's rescaled by `10^(buy - sell decimals)`
+        let pow10 = |exponent: u32| {
+            10u128
+                .checked_pow(exponent)
+                .ok_or(ProgramError::ArithmeticOverflow)
+        };
+        let (numerator_scale, denominator_scale) = if buy_decimals >= sell_decimals {
+            (
+                pow10((buy_decimals - sell_decimals) as u32)?,
+                pow10(self.decimals as u32)?,
+            )
+        } else {
+            (
+                1,
+                pow10(self.decimals as u32 + (sell_decimals - buy_decimals) as u32)?,
+            )
+        };
+        let oracle_amount = (fill_amount as u128)
+            .checked_mul(self.price as u128)
+            .and_then(|amount| amount.checked_mul(numerator_scale))
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / denominator_scale;
+        let min_buy_amount = oracle_amount
+            .checked_mul((MAX_BPS - max_deviation_bps) as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / MAX_BPS as u128;
+
+        if (buy_amount as u128) < min_buy_amount {
+            return Err(SolverError::OraclePriceDeviation.into());
+        }
+        Ok(())
+    }
+}
@@ -1,9 +1,19 @@
-use crate::{error::SolverError, utils::Unpackable};
+use crate::{
+    error::SolverError,
+    state::curve::{exponential_decay, interpolate, AuctionCurve, Breakpoint, MAX_BREAKPOINTS},
+    utils::{
+        token::{check_token_account, NATIVE_MINT},
+        Unpackable,
+    },
+};
 use bytemuck::{Pod, Zeroable};
 use light_hasher::{Hasher, Keccak};
 use pinocchio::{
+    account_info::AccountInfo,
+    instruction::Seed,
     program_error::ProgramError,
     pubkey::{create_program_address, Pubkey},
+    ProgramResult,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
@@ -11,6 +21,8 @@ use pinocchio::{
 pub struct Order {
     pub from_token_account: Pubkey,
     pub to_token_account: Pubkey,
+    pub sell_mint: Pubkey,
+    pub buy_mint: Pubkey,
     pub sell_amount: u64,
     pub buy_amount: u64,
     pub referral_fee: u64,
@@ -19,8 +31,126 @@ pub struct Order {
     pub amount_decrease_per_second: u64,
     pub start_time: u64,
     pub deadline: u64,
+    /// Replay protection, each nonce of an owner can be filled once.
+    pub nonce: u64,
+    /// Half-life of an exponential curve, or step length of a stepwise curve, in seconds.
+    pub curve_period: u64,
+    /// Points of a piecewise-linear curve, only the first `breakpoint_count` are used.
+    pub breakpoints: [Breakpoint; MAX_BREAKPOINTS],
+    /// Pyth `PriceUpdateV2` account guarding fills against a lagging curve, unset to
+    /// disable the guard.
+    pub oracle: Pubkey,
+    /// How far below the oracle price a fill may land.
+    pub oracle_max_deviation_bps: u64,
+    /// Maximum age of the oracle price, in seconds.
+    pub oracle_max_staleness: u64,
+    /// Pyth feed id `oracle` must hold, so a price update of another feed can't stand in.
+    pub oracle_feed_id: [u8; 32],
+    /// How the sell tokens are secured, see [`OrderMode`].
+    pub mode: u8,
+    /// How `referral_fee` is interpreted, see [`FeeKind`].
+    pub referral_fee_kind: u8,
+    /// Whether solvers may fill a fraction of `sell_amount` at a time.
+    pub allow_partial_fill: u8,
+    /// How the buy amount decays over the auction, see [`AuctionCurve`].
+    pub curve: u8,
+    pub breakpoint_count: u8,
+    /// How the required buy amount is priced, see [`OrderType`].
+    pub order_type: u8,
+    /// Whether the solver's route runs with the order's signer authority. Only for
+    /// integrations that need the order PDA to sign, any allow-listed program then acts
+    /// with it. Rejected for signed orders, whose authority is shared by the owner's orders.
+    pub sign_route: u8,
+    /// Whether the owner sells SOL: `from_token_account` is the owner's wallet and
+    /// `Initialize` wraps `sell_amount` lamports into the wSOL vault. Escrow only.
+    pub native_sell: u8,
+    /// Whether the owner buys SOL: `to_token_account` is the owner's wallet, and the route
+    /// delivers wSOL to the order signer's wSOL associated token account, unwrapped to the
+    /// owner after the route.
+    pub native_buy: u8,
+    pub _padding: [u8; 7],
+}
+
+/// Data of an on-chain order account: the published intent followed by its fill progress.
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct OrderAccount {
+    pub order: Order,
+    pub filled_sell_amount: u64,
+}
+
+/// How the order PDA gets authority over the sell tokens.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderMode {
+    /// The sell tokens are moved into a vault owned by the order PDA.
+    Escrow,
+    /// The sell tokens stay in the owner's account, which approves the order PDA as delegate.
+    Delegate,
+}
+
+impl TryFrom<u8> for OrderMode {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(OrderMode::Escrow),
+            1 => Ok(OrderMode::Delegate),
+            _ => Err(SolverError::InvalidOrderMode.into()),
+        }
+    }
+}
+
+/// How the required buy amount of an order is priced.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrderType {
+    /// Dutch auction along the order's [`AuctionCurve`].
+    DutchAuction,
+    /// Exactly `buy_amount` at any time within `[start_time, deadline]`, the curve fields
+    /// and `minimun_buy_amount` must be left unset.
+    Limit,
+}
+
+impl TryFrom<u8> for OrderType {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(OrderType::DutchAuction),
+            1 => Ok(OrderType::Limit),
+            _ => Err(SolverError::InvalidOrderType.into()),
+        }
+    }
+}
+
+/// How a fee amount stored in an order is interpreted.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeeKind {
+    /// Basis points of the filled sell amount.
+    Bps,
+    /// Absolute amount of sell tokens for the whole order, split pro-rata over partial fills.
+    Absolute,
+}
+
+impl TryFrom<u8> for FeeKind {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FeeKind::Bps),
+            1 => Ok(FeeKind::Absolute),
+            _ => Err(SolverError::InvalidReferralFee.into()),
+        }
+    }
 }
 
+pub const MAX_BPS: u64 = 10_000;
+
+/// Seed of the per-owner authority PDA that executes off-chain signed orders.
+pub const AUTHORITY_SEED: &[u8] = b"authority";
+
 impl Order {
     pub fn validate_and_unpack(
         data: &[u8],
@@ -29,10 +159,12 @@ impl Order {
         order_bump: u8,
     ) -> Result<(Self, [u8; 32]), ProgramError> {
         // 2. Validate Order PDA
-        let intent_hash = Keccak::hashv(&[data]).unwrap();
+        let intent_hash = Keccak::hashv(&[data])
+            .map_err(|_| ProgramError::from(SolverError::InvalidInstructionData))?;
         // Use correct slice type for address generation
         let intent_hash_bytes = &intent_hash as &[u8];
 
+        // A bump landing on the curve derives no address at all
         let calculated_order_pubkey = create_program_address(
             &[
                 b"order",
@@ -42,41 +174,343 @@ impl Order {
             ],
             &crate::ID,
         )
-        .unwrap();
+        .map_err(|_| ProgramError::from(SolverError::InvalidPda))?;
 
         if &calculated_order_pubkey != order_key {
             return Err(SolverError::InvalidOrderAccount.into());
         }
 
-        let order = Order::unpack(data).unwrap();
+        let order = Order::unpack(data)?;
 
         Ok((order, intent_hash)) // Return the intent_hash for further use
     }
 
-    pub fn calculate_current_buy_amount(&self, current_time: u64) -> u64 {
+    /// Unpacks an order signed off-chain by `owner_key`, checking that `authority_key` is
+    /// the owner's authority PDA `[b"authority", owner, bump]`.
+    pub fn unpack_signed(
+        data: &[u8],
+        owner_key: &Pubkey,
+        authority_key: &Pubkey,
+        authority_bump: u8,
+    ) -> Result<Self, ProgramError> {
+        let calculated_authority_pubkey = create_program_address(
+            &[AUTHORITY_SEED, owner_key.as_ref(), &[authority_bump]],
+            &crate::ID,
+        )
+        .map_err(|_| ProgramError::from(SolverError::InvalidPda))?;
+
+        if &calculated_authority_pubkey != authority_key {
+            return Err(SolverError::InvalidOrderAccount.into());
+        }
+
+        Order::unpack(data)
+    }
+
+    /// Signer seeds of the order PDA: `[b"order", owner, intent_hash, bump]`.
+    pub fn signer_seeds<'a>(
+        owner_key: &'a Pubkey,
+        intent_hash: &'a [u8; 32],
+        order_bump: &'a u8,
+    ) -> [Seed<'a>; 4] {
+        [
+            Seed::from(b"order".as_slice()),
+            Seed::from(owner_key),
+            Seed::from(intent_hash.as_ref()),
+            Seed::from(core::slice::from_ref(order_bump)),
+        ]
+    }
+
+    /// Signer seeds of the authority PDA: `[b"authority", owner, bump]`.
+    pub fn authority_signer_seeds<'a>(
+        owner_key: &'a Pubkey,
+        authority_bump: &'a u8,
+    ) -> [Seed<'a>; 3] {
+        [
+            Seed::from(AUTHORITY_SEED),
+            Seed::from(owner_key),
+            Seed::from(core::slice::from_ref(authority_bump)),
+        ]
+    }
+
+    pub fn mode(&self) -> Result<OrderMode, ProgramError> {
+        OrderMode::try_from(self.mode)
+    }
+
+    /// Referral fee owed on a fill of `fill_amount` sell tokens after `filled_sell_amount`
+    /// were already filled. An absolute fee is spread pro-rata over the fills, rounded down
+    /// so the fill completing the order pays the remainder.
+    pub fn referral_fee_amount(
+        &self,
+        filled_sell_amount: u64,
+        fill_amount: u64,
+    ) -> Result<u64, ProgramError> {
+        let fee = match FeeKind::try_from(self.referral_fee_kind)? {
+            FeeKind::Bps => {
+                if self.referral_fee > MAX_BPS {
+                    return Err(SolverError::InvalidReferralFee.into());
+                }
+                (fill_amount as u128)
+                    .checked_mul(self.referral_fee as u128)
+                    .ok_or(ProgramError::ArithmeticOverflow)?
+                    / MAX_BPS as u128
+            }
+            FeeKind::Absolute => {
+                if self.referral_fee > self.sell_amount {
+                    return Err(SolverError::InvalidReferralFee.into());
+                }
+                let due = |sold: u64| {
+                    (self.referral_fee as u128 * sold as u128)
+                        .checked_div(self.sell_amount as u128)
+                        .ok_or(ProgramError::ArithmeticOverflow)
+                };
+                let filled_after = filled_sell_amount
+                    .checked_add(fill_amount)
+                    .ok_or(ProgramError::ArithmeticOverflow)?;
+                due(filled_after)? - due(filled_sell_amount)?
+            }
+        };
+
+        if fee > fill_amount as u128 {
+            return Err(SolverError::InvalidReferralFee.into());
+        }
+
+        Ok(fee as u64)
+    }
+
+    pub fn order_type(&self) -> Result<OrderType, ProgramError> {
+        OrderType::try_from(self.order_type)
+    }
+
+    /// Checks that the pricing fields are consistent with the order type.
+    pub fn check_price_parameters(&self) -> Result<(), ProgramError> {
+        match self.order_type()? {
+            OrderType::DutchAuction => self.check_curve_parameters().map(|_| ()),
+            OrderType::Limit => {
+                let curve_unset = self.curve == AuctionCurve::Linear as u8
+                    && self.minimun_buy_amount == 0
+                    && self.amount_decrease_per_second == 0
+                    && self.curve_period == 0
+                    && self.breakpoint_count == 0;
+                if !curve_unset {
+                    return Err(SolverError::InvalidCurveParameters.into());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Checks that the curve fields agree with the auction curve: the floor can't exceed the
+    /// start amount, each curve only sets the parameters it uses, and breakpoints move
+    /// forward in time without raising the price.
+    pub fn check_curve_parameters(&self) -> Result<AuctionCurve, ProgramError> {
+        let curve = AuctionCurve::try_from(self.curve)?;
+
+        let uses_rate = curve == AuctionCurve::PerSecond;
+        let uses_period = matches!(curve, AuctionCurve::Exponential | AuctionCurve::Stepwise);
+        let uses_breakpoints = curve == AuctionCurve::PiecewiseLinear;
+
+        let consistent = self.minimun_buy_amount <= self.buy_amount
+            && uses_rate == (self.amount_decrease_per_second > 0)
+            && uses_period == (self.curve_period > 0)
+            && uses_breakpoints == (self.breakpoint_count > 0)
+            && self.breakpoint_count as usize <= MAX_BREAKPOINTS;
+        if !consistent {
+            return Err(SolverError::InvalidCurveParameters.into());
+        }
+
+        let duration = self.deadline.saturating_sub(self.start_time);
+        let mut previous = Breakpoint {
+            time_offset: 0,
+            buy_amount: self.buy_amount,
+        };
+        for breakpoint in self.active_breakpoints() {
+            if breakpoint.time_offset <= previous.time_offset
+                || breakpoint.time_offset >= duration
+                || breakpoint.buy_amount > previous.buy_amount
+                || breakpoint.buy_amount < self.minimun_buy_amount
+            {
+                return Err(SolverError::InvalidCurveParameters.into());
+            }
+            previous = *breakpoint;
+        }
+
+        Ok(curve)
+    }
+
+    fn active_breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints[..(self.breakpoint_count as usize).min(MAX_BREAKPOINTS)]
+    }
+
+    pub fn calculate_current_buy_amount(&self, current_time: u64) -> Result<u64, ProgramError> {
+        if self.order_type()? == OrderType::Limit {
+            self.check_price_parameters()?;
+            return Ok(self.buy_amount);
+        }
+
+        let curve = self.check_curve_parameters()?;
+
         // 1. If auction hasn't started, return the full starting buy_amount
         if current_time <= self.start_time {
-            return self.buy_amount;
+            return Ok(self.buy_amount);
         }
 
-        // 2. If auction has ended, return the floor (minimum)
-        if current_time >= self.deadline {
-            return self.minimun_buy_amount;
+        let elapsed_time = current_time - self.start_time;
+        let duration = self.deadline.saturating_sub(self.start_time);
+        let range = self.buy_amount - self.minimun_buy_amount;
+
+        let start = Breakpoint {
+            time_offset: 0,
+            buy_amount: self.buy_amount,
+        };
+        let end = Breakpoint {
+            time_offset: duration,
+            buy_amount: self.minimun_buy_amount,
+        };
+
+        // 2. Curves ending at the deadline return the floor (minimum) once it has passed
+        let ends_at_deadline = matches!(
+            curve,
+            AuctionCurve::Linear | AuctionCurve::Stepwise | AuctionCurve::PiecewiseLinear
+        );
+        if ends_at_deadline && elapsed_time >= duration {
+            return Ok(self.minimun_buy_amount);
         }
 
-        // 3. Calculate linear decay
-        let total_duration = self.deadline.saturating_sub(self.start_time);
-        let elapsed_time = current_time.saturating_sub(self.start_time);
+        // 3. Calculate the decay along the curve
+        let current = match curve {
+            AuctionCurve::Linear => interpolate(start, end, elapsed_time),
+            AuctionCurve::PerSecond => {
+                let reduction = self.amount_decrease_per_second.saturating_mul(elapsed_time);
+                self.buy_amount
+                    .saturating_sub(reduction)
+                    .max(self.minimun_buy_amount)
+            }
+            AuctionCurve::Exponential => {
+                self.minimun_buy_amount + exponential_decay(range, elapsed_time, self.curve_period)
+            }
+            AuctionCurve::Stepwise => {
+                let step_start = elapsed_time - elapsed_time % self.curve_period;
+                interpolate(start, end, step_start)
+            }
+            AuctionCurve::PiecewiseLinear => {
+                let mut from = start;
+                let mut current = None;
+                for to in self.active_breakpoints().iter().chain([&end]) {
+                    if elapsed_time < to.time_offset {
+                        current = Some(interpolate(from, *to, elapsed_time));
+                        break;
+                    }
+                    from = *to;
+                }
+                current.unwrap_or(self.minimun_buy_amount)
+            }
+        };
 
-        // Range of the auction price
-        let total_decay_range = self.buy_amount.saturating_sub(self.minimun_buy_amount);
+        Ok(current)
+    }
 
-        // We calculate (Range * Elapsed) / Total to maintain precision with integers
-        let reduction = (total_decay_range as u128)
-            .saturating_mul(elapsed_time as u128)
-            .saturating_div(total_duration as u128) as u64;
+    /// Checks that the order can be filled at `current_time`, i.e. within
+    /// `[start_time, deadline]`.
+    pub fn check_time_window(&self, current_time: u64) -> Result<(), ProgramError> {
+        if current_time < self.start_time {
+            return Err(SolverError::OrderNotStarted.into());
+        }
+        if current_time > self.deadline {
+            return Err(SolverError::OrderExpired.into());
+        }
+        Ok(())
+    }
+
+    /// Checks that `fill_amount` sell tokens can be filled on top of `filled_sell_amount`.
+    /// Orders without `allow_partial_fill` must be filled in one go.
+    pub fn check_fill_amount(
+        &self,
+        filled_sell_amount: u64,
+        fill_amount: u64,
+    ) -> Result<(), ProgramError> {
+        let remaining = self
+            .sell_amount
+            .checked_sub(filled_sell_amount)
+            .ok_or(SolverError::InvalidFillAmount)?;
+
+        if fill_amount == 0
+            || fill_amount > remaining
+            || (self.allow_partial_fill == 0 && fill_amount != remaining)
+        {
+            return Err(SolverError::InvalidFillAmount.into());
+        }
+
+        Ok(())
+    }
+
+    /// Buy amount owed for `fill_amount` sell tokens at `current_time`: the pro-rata share
+    /// of [`Order::calculate_current_buy_amount`], rounded up in favor of the owner.
+    pub fn required_buy_amount(
+        &self,
+        current_time: u64,
+        fill_amount: u64,
+    ) -> Result<u64, ProgramError> {
+        if self.sell_amount == 0 {
+            return Err(SolverError::InvalidFillAmount.into());
+        }
+
+        let buy_amount = (self.calculate_current_buy_amount(current_time)? as u128)
+            .checked_mul(fill_amount as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            .div_ceil(self.sell_amount as u128);
 
-        self.buy_amount.saturating_sub(reduction)
+        u64::try_from(buy_amount).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
+    /// Checks that native SOL sides use the owner's wallet, and that native sell orders
+    /// escrow the wrapped SOL.
+    pub fn check_native_accounts(&self, owner: &Pubkey) -> Result<(), ProgramError> {
+        if self.native_sell != 0 {
+            if self.mode()? != OrderMode::Escrow {
+                return Err(SolverError::InvalidOrderMode.into());
+            }
+            if &self.from_token_account != owner {
+                return Err(SolverError::InvalidOrderAccounts.into());
+            }
+            if self.sell_mint != NATIVE_MINT {
+                return Err(SolverError::InvalidTokenAccountMint.into());
+            }
+        }
+        if self.native_buy != 0 {
+            if &self.to_token_account != owner {
+                return Err(SolverError::InvalidOrderAccounts.into());
+            }
+            if self.buy_mint != NATIVE_MINT {
+                return Err(SolverError::InvalidTokenAccountMint.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the token accounts of the order hold its mints and belong to `owner`.
+    /// Native SOL sides are the owner's wallet, and the referrer may take the fee into any
+    /// account of the sell mint.
+    pub fn check_token_accounts(
+        &self,
+        owner: &Pubkey,
+        sell_mint: &AccountInfo,
+        from_token_account: &AccountInfo,
+        to_token_account: &AccountInfo,
+        referral_token_account: &AccountInfo,
+    ) -> ProgramResult {
+        if sell_mint.key() != &self.sell_mint {
+            return Err(SolverError::InvalidTokenAccountMint.into());
+        }
+        if self.native_sell == 0 {
+            check_token_account(from_token_account, &self.sell_mint, Some(owner))?;
+        }
+        if self.native_buy == 0 {
+            check_token_account(to_token_account, &self.buy_mint, Some(owner))?;
+        }
+        if self.referral_fee > 0 {
+            check_token_account(referral_token_account, &self.sell_mint, None)?;
+        }
+        Ok(())
     }
 
     pub fn validate_order_accounts(
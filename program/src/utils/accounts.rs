@@ -0,0 +1,49 @@
+use crate::error::SolverError;
+use pinocchio::{
+    account_info::AccountInfo, program_error::ProgramError, pubkey::Pubkey, ProgramResult,
+};
+
+/// Checks that `account` signed the transaction.
+pub fn check_signer(account: &AccountInfo) -> ProgramResult {
+    if !account.is_signer() {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+/// Checks that `account` is writable, failing with `error` otherwise.
+pub fn check_writable(account: &AccountInfo, error: SolverError) -> ProgramResult {
+    if !account.is_writable() {
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+/// Checks that `account` is the program `program_id`.
+pub fn check_program(account: &AccountInfo, program_id: &Pubkey) -> ProgramResult {
+    if account.key() != program_id {
+        return Err(SolverError::InvalidProgramId.into());
+    }
+    Ok(())
+}
+
+/// Checks that `account` is owned by `owner`, failing with `error` otherwise.
+pub fn check_owner(account: &AccountInfo, owner: &Pubkey, error: SolverError) -> ProgramResult {
+    if !account.is_owned_by(owner) {
+        return Err(error.into());
+    }
+    Ok(())
+}
+
+/// Checks that no account is passed twice, so one account can't stand in for two roles.
+pub fn check_distinct(accounts: &[&AccountInfo]) -> ProgramResult {
+    for (i, account) in accounts.iter().enumerate() {
+        if accounts[i + 1..]
+            .iter()
+            .any(|other| other.key() == account.key())
+        {
+            return Err(SolverError::DuplicateAccount.into());
+        }
+    }
+    Ok(())
+}
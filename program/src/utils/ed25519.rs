@@ -0,0 +1,75 @@
+use crate::error::SolverError;
+use pinocchio::{
+    account_info::AccountInfo,
+    pubkey::{Pubkey, PUBKEY_BYTES},
+    sysvars::instructions::Instructions,
+    ProgramResult,
+};
+
+/// Ed25519 signature verification precompile `Ed25519SigVerify111111111111111111111111111`.
+pub const ED25519_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("Ed25519SigVerify111111111111111111111111111");
+
+/// Size of the header (signature count + padding) of an Ed25519 instruction.
+const HEADER_LEN: usize = 2;
+
+/// Size of one `Ed25519SignatureOffsets` entry.
+const OFFSETS_LEN: usize = 14;
+
+/// Instruction index meaning "the Ed25519 instruction itself".
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Checks that the instruction right before the current one is an Ed25519 precompile
+/// instruction verifying a single signature of `signer` over exactly `message`.
+///
+/// The precompile fails the whole transaction on a bad signature, so this only has to
+/// check that the verified key and message are the expected ones and that they are read
+/// from the Ed25519 instruction itself.
+pub fn verify_ed25519_instruction(
+    instructions_sysvar: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> ProgramResult {
+    let instructions = Instructions::try_from(instructions_sysvar)?;
+    let instruction = instructions
+        .get_instruction_relative(-1)
+        .map_err(|_| SolverError::InvalidIntentSignature)?;
+
+    if instruction.get_program_id() != &ED25519_PROGRAM_ID {
+        return Err(SolverError::InvalidIntentSignature.into());
+    }
+
+    let data = instruction.get_instruction_data();
+    if data.len() < HEADER_LEN + OFFSETS_LEN || data[0] != 1 {
+        return Err(SolverError::InvalidIntentSignature.into());
+    }
+
+    // Ed25519SignatureOffsets, all little-endian u16
+    let offsets = &data[HEADER_LEN..HEADER_LEN + OFFSETS_LEN];
+    let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+    let signature_instruction_index = read_u16(2);
+    let public_key_offset = read_u16(4) as usize;
+    let public_key_instruction_index = read_u16(6);
+    let message_data_offset = read_u16(8) as usize;
+    let message_data_size = read_u16(10) as usize;
+    let message_instruction_index = read_u16(12);
+
+    if signature_instruction_index != CURRENT_INSTRUCTION
+        || public_key_instruction_index != CURRENT_INSTRUCTION
+        || message_instruction_index != CURRENT_INSTRUCTION
+    {
+        return Err(SolverError::InvalidIntentSignature.into());
+    }
+
+    let public_key = data.get(public_key_offset..public_key_offset + PUBKEY_BYTES);
+    if public_key != Some(signer.as_slice()) {
+        return Err(SolverError::InvalidIntentSignature.into());
+    }
+
+    let signed_message = data.get(message_data_offset..message_data_offset + message_data_size);
+    if signed_message != Some(message) {
+        return Err(SolverError::InvalidIntentSignature.into());
+    }
+
+    Ok(())
+}
@@ -1,10 +1,10 @@
+pub mod accounts;
+pub mod ed25519;
+pub mod token;
+
 use crate::error::SolverError;
 use bytemuck::Pod;
-use pinocchio::{
-    account_info::AccountInfo,
-    program_error::ProgramError,
-    pubkey::{find_program_address, Pubkey},
-};
+use pinocchio::{account_info::AccountInfo, program_error::ProgramError, ProgramResult};
 
 pub trait DataLen {
     const LEN: usize;
@@ -17,9 +17,10 @@ impl<T: Pod> DataLen for T {
 pub trait Unpackable: Pod {
     fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
         if data.len() != Self::LEN {
-            return Err(ProgramError::InvalidInstructionData);
+            return Err(SolverError::InvalidInstructionData.into());
         }
-        Ok(bytemuck::try_pod_read_unaligned(data).unwrap())
+        bytemuck::try_pod_read_unaligned(data)
+            .map_err(|_| SolverError::InvalidInstructionData.into())
     }
 }
 
@@ -69,46 +70,16 @@ pub unsafe fn load_acc_mut_unchecked<T: DataLen>(bytes: &mut [u8]) -> Result<&mu
     Ok(&mut *(bytes.as_mut_ptr() as *mut T))
 }
 
-#[inline(always)]
-pub unsafe fn load_ix_data<T: DataLen>(bytes: &[u8]) -> Result<&T, ProgramError> {
-    if bytes.len() != T::LEN {
-        return Err(SolverError::InvalidInstructionData.into());
-    }
-    Ok(&*(bytes.as_ptr() as *const T))
-}
-
-pub unsafe fn to_bytes<T: DataLen>(data: &T) -> &[u8] {
-    core::slice::from_raw_parts(data as *const T as *const u8, T::LEN)
-}
-
-pub unsafe fn to_mut_bytes<T: DataLen>(data: &mut T) -> &mut [u8] {
-    core::slice::from_raw_parts_mut(data as *mut T as *mut u8, T::LEN)
-}
-
-pub unsafe fn try_from_account_info<T: DataLen>(acc: &AccountInfo) -> Result<&T, ProgramError> {
-    if acc.owner() != &crate::ID {
-        return Err(ProgramError::IllegalOwner);
-    }
-    let bytes = acc.try_borrow_data()?;
-
-    if bytes.len() != T::LEN {
-        return Err(ProgramError::InvalidAccountData);
-    }
-    Ok(&*(bytes.as_ptr() as *const T))
-}
-
-pub unsafe fn try_from_account_info_mut<T: DataLen>(
-    acc: &AccountInfo,
-) -> Result<&mut T, ProgramError> {
-    if acc.owner() != &crate::ID {
-        return Err(ProgramError::IllegalOwner);
+/// Moves all lamports of a program-owned account to `destination` and closes it.
+pub fn close_program_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    {
+        let mut destination_lamports = destination.try_borrow_mut_lamports()?;
+        let mut account_lamports = account.try_borrow_mut_lamports()?;
+        *destination_lamports = destination_lamports
+            .checked_add(*account_lamports)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        *account_lamports = 0;
     }
 
-    let mut bytes = acc.try_borrow_mut_data()?;
-
-    if bytes.len() != T::LEN {
-        return Err(ProgramError::InvalidAccountData);
-    }
-
-    Ok(&mut *(bytes.as_mut_ptr() as *mut T))
+    account.close()
 }
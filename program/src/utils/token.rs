@@ -0,0 +1,409 @@
+use crate::error::SolverError;
+use alloc::vec::Vec;
+use pinocchio::{
+    account_info::{AccountInfo, Ref},
+    cpi::slice_invoke_signed,
+    instruction::{AccountMeta, Instruction, Signer},
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+use pinocchio_token::state::{Mint, TokenAccount};
+
+/// Associated Token Account program ID `ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL`.
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Mint of wrapped SOL `So11111111111111111111111111111111111111112`, owned by the SPL
+/// Token program.
+pub const NATIVE_MINT: Pubkey =
+    pinocchio_pubkey::pubkey!("So11111111111111111111111111111111111111112");
+
+/// SPL Token-2022 program ID `TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb`.
+pub const TOKEN_2022_PROGRAM_ID: Pubkey =
+    pinocchio_pubkey::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+/// Token-2022 accounts with extensions store their type right after the base token account
+/// layout, mints being padded up to it, followed by the extensions as TLV entries.
+const ACCOUNT_TYPE_OFFSET: usize = TokenAccount::LEN;
+const MINT_ACCOUNT_TYPE: u8 = 1;
+const TOKEN_ACCOUNT_TYPE: u8 = 2;
+
+const TRANSFER_FEE_CONFIG_EXTENSION: u16 = 1;
+const MAX_FEE_BASIS_POINTS: u128 = 10_000;
+
+pub fn is_token_program(program: &Pubkey) -> bool {
+    program == &pinocchio_token::ID || program == &TOKEN_2022_PROGRAM_ID
+}
+
+/// Checks that `token_program` is SPL Token or Token-2022.
+pub fn check_token_program(token_program: &AccountInfo) -> ProgramResult {
+    if !is_token_program(token_program.key()) {
+        return Err(SolverError::InvalidTokenProgram.into());
+    }
+    Ok(())
+}
+
+/// Checks that `account` is owned by a token program and holds a `base_len` state, followed
+/// by Token-2022 extensions of an `account_type` account.
+fn check_layout(account: &AccountInfo, base_len: usize, account_type: u8) -> ProgramResult {
+    if account.is_owned_by(&pinocchio_token::ID) && account.data_len() == base_len {
+        return Ok(());
+    }
+
+    if account.is_owned_by(&TOKEN_2022_PROGRAM_ID) {
+        if account.data_len() == base_len {
+            return Ok(());
+        }
+        let data = account.try_borrow_data()?;
+        if data.len() > ACCOUNT_TYPE_OFFSET && data[ACCOUNT_TYPE_OFFSET] == account_type {
+            return Ok(());
+        }
+    }
+
+    Err(ProgramError::InvalidAccountData)
+}
+
+/// Reads a token account of either token program, ignoring Token-2022 extensions.
+pub fn load_token_account(account: &AccountInfo) -> Result<Ref<'_, TokenAccount>, ProgramError> {
+    check_layout(account, TokenAccount::LEN, TOKEN_ACCOUNT_TYPE)
+        .map_err(|_| ProgramError::from(SolverError::InvalidTokenAccount))?;
+    Ok(Ref::map(account.try_borrow_data()?, |data| unsafe {
+        TokenAccount::from_bytes_unchecked(data)
+    }))
+}
+
+/// Checks that `account` is a token account of `mint`, held by `owner` when given.
+pub fn check_token_account(
+    account: &AccountInfo,
+    mint: &Pubkey,
+    owner: Option<&Pubkey>,
+) -> ProgramResult {
+    if !account.is_owned_by(&pinocchio_token::ID) && !account.is_owned_by(&TOKEN_2022_PROGRAM_ID) {
+        return Err(SolverError::InvalidTokenAccountOwner.into());
+    }
+
+    let token_account = load_token_account(account)?;
+    if token_account.mint() != mint {
+        return Err(SolverError::InvalidTokenAccountMint.into());
+    }
+    if owner.is_some_and(|owner| token_account.owner() != owner) {
+        return Err(SolverError::InvalidTokenAccountOwner.into());
+    }
+    Ok(())
+}
+
+/// Reads a mint of either token program, ignoring Token-2022 extensions.
+pub fn load_mint(account: &AccountInfo) -> Result<Ref<'_, Mint>, ProgramError> {
+    check_layout(account, Mint::LEN, MINT_ACCOUNT_TYPE)?;
+    Ok(Ref::map(account.try_borrow_data()?, |data| unsafe {
+        Mint::from_bytes_unchecked(data)
+    }))
+}
+
+/// Value of the `extension_type` TLV entry of a Token-2022 account.
+fn find_extension(data: &[u8], extension_type: u16) -> Option<&[u8]> {
+    let mut tlv = data.get(ACCOUNT_TYPE_OFFSET + 1..)?;
+    while tlv.len() >= 4 {
+        let kind = u16::from_le_bytes([tlv[0], tlv[1]]);
+        let len = u16::from_le_bytes([tlv[2], tlv[3]]) as usize;
+        // An uninitialized entry marks the end of the extensions
+        if kind == 0 {
+            return None;
+        }
+        let value = tlv.get(4..4 + len)?;
+        if kind == extension_type {
+            return Some(value);
+        }
+        tlv = &tlv[4 + len..];
+    }
+    None
+}
+
+/// Transfer fee a Token-2022 mint charges in one epoch, withheld on the destination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransferFee {
+    pub maximum_fee: u64,
+    pub basis_points: u16,
+}
+
+impl TransferFee {
+    /// Reads the fee `mint` charges in `epoch`, `None` without the transfer fee extension.
+    pub fn load(mint: &AccountInfo, epoch: u64) -> Result<Option<Self>, ProgramError> {
+        check_layout(mint, Mint::LEN, MINT_ACCOUNT_TYPE)?;
+        let data = mint.try_borrow_data()?;
+        let Some(config) = find_extension(&data, TRANSFER_FEE_CONFIG_EXTENSION) else {
+            return Ok(None);
+        };
+
+        // Authorities and withheld amount, then the older and newer fees
+        let read_fee = |offset: usize| -> Option<(u64, Self)> {
+            let fee = config.get(offset..offset + 18)?;
+            Some((
+                u64::from_le_bytes(fee[0..8].try_into().ok()?),
+                Self {
+                    maximum_fee: u64::from_le_bytes(fee[8..16].try_into().ok()?),
+                    basis_points: u16::from_le_bytes([fee[16], fee[17]]),
+                },
+            ))
+        };
+        let (_, older) = read_fee(72).ok_or(ProgramError::InvalidAccountData)?;
+        let (newer_epoch, newer) = read_fee(90).ok_or(ProgramError::InvalidAccountData)?;
+
+        Ok(Some(if epoch >= newer_epoch { newer } else { older }))
+    }
+
+    /// Fee withheld on a transfer of `amount`, rounded up like the token program.
+    pub fn fee(&self, amount: u64) -> u64 {
+        let fee = (amount as u128 * self.basis_points as u128).div_ceil(MAX_FEE_BASIS_POINTS);
+        fee.min(self.maximum_fee as u128) as u64
+    }
+
+    /// Smallest amount to transfer for `net_amount` to arrive after the fee.
+    pub fn gross_amount(&self, net_amount: u64) -> Result<u64, ProgramError> {
+        let capped = net_amount
+            .checked_add(self.maximum_fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if self.basis_points as u128 >= MAX_FEE_BASIS_POINTS {
+            return Ok(capped);
+        }
+
+        let estimate = (net_amount as u128 * MAX_FEE_BASIS_POINTS)
+            .div_ceil(MAX_FEE_BASIS_POINTS - self.basis_points as u128);
+        let mut gross = u64::try_from(estimate).unwrap_or(u64::MAX).min(capped);
+
+        // The fee is rounded up, so settle the estimate within a few units
+        while gross - self.fee(gross) < net_amount {
+            gross = gross
+                .checked_add(1)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+        while gross > net_amount && gross - 1 - self.fee(gross - 1) >= net_amount {
+            gross -= 1;
+        }
+
+        Ok(gross)
+    }
+}
+
+/// Runs a token instruction of `token_program` over `accounts`, followed by
+/// `extra_accounts` such as the accounts of a transfer hook.
+fn invoke_token_instruction(
+    token_program: &AccountInfo,
+    accounts: &[(&AccountInfo, bool, bool)],
+    extra_accounts: &[AccountInfo],
+    data: &[u8],
+    signers: &[Signer],
+) -> ProgramResult {
+    let mut account_metas = Vec::with_capacity(accounts.len() + extra_accounts.len());
+    let mut account_infos = Vec::with_capacity(accounts.len() + extra_accounts.len());
+    for (account, is_writable, is_signer) in accounts {
+        account_metas.push(AccountMeta::new(account.key(), *is_writable, *is_signer));
+        account_infos.push(*account);
+    }
+    for account in extra_accounts {
+        account_metas.push(AccountMeta::new(
+            account.key(),
+            account.is_writable(),
+            account.is_signer(),
+        ));
+        account_infos.push(account);
+    }
+
+    let instruction = Instruction {
+        program_id: token_program.key(),
+        accounts: &account_metas,
+        data,
+    };
+
+    slice_invoke_signed(&instruction, &account_infos, signers)
+}
+
+/// `TransferChecked` of either token program. Token-2022 requires it for mints with
+/// transfer fees or hooks, the hook's extra accounts being passed as `hook_accounts`.
+pub struct TransferChecked<'a> {
+    pub from: &'a AccountInfo,
+    pub mint: &'a AccountInfo,
+    pub to: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub amount: u64,
+    pub decimals: u8,
+    pub token_program: &'a AccountInfo,
+    pub hook_accounts: &'a [AccountInfo],
+}
+
+impl TransferChecked<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let mut data = [0u8; 10];
+        data[0] = 12;
+        data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        data[9] = self.decimals;
+
+        invoke_token_instruction(
+            self.token_program,
+            &[
+                (self.from, true, false),
+                (self.mint, false, false),
+                (self.to, true, false),
+                (self.authority, false, true),
+            ],
+            self.hook_accounts,
+            &data,
+            signers,
+        )
+    }
+}
+
+/// `Approve` of either token program.
+pub struct Approve<'a> {
+    pub source: &'a AccountInfo,
+    pub delegate: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub amount: u64,
+    pub token_program: &'a AccountInfo,
+}
+
+impl Approve<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        let mut data = [0u8; 9];
+        data[0] = 4;
+        data[1..].copy_from_slice(&self.amount.to_le_bytes());
+
+        invoke_token_instruction(
+            self.token_program,
+            &[
+                (self.source, true, false),
+                (self.delegate, false, false),
+                (self.authority, false, true),
+            ],
+            &[],
+            &data,
+            &[],
+        )
+    }
+}
+
+/// `Revoke` of either token program.
+pub struct Revoke<'a> {
+    pub source: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+impl Revoke<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        invoke_token_instruction(
+            self.token_program,
+            &[(self.source, true, false), (self.authority, false, true)],
+            &[],
+            &[5],
+            &[],
+        )
+    }
+}
+
+/// `SyncNative` of either token program, crediting lamports sent to a wSOL account.
+pub struct SyncNative<'a> {
+    pub native_token: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+impl SyncNative<'_> {
+    pub fn invoke(&self) -> ProgramResult {
+        invoke_token_instruction(
+            self.token_program,
+            &[(self.native_token, true, false)],
+            &[],
+            &[17],
+            &[],
+        )
+    }
+}
+
+/// `CloseAccount` of either token program.
+pub struct CloseAccount<'a> {
+    pub account: &'a AccountInfo,
+    pub destination: &'a AccountInfo,
+    pub authority: &'a AccountInfo,
+    pub token_program: &'a AccountInfo,
+}
+
+impl CloseAccount<'_> {
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        invoke_token_instruction(
+            self.token_program,
+            &[
+                (self.account, true, false),
+                (self.destination, true, false),
+                (self.authority, false, true),
+            ],
+            &[],
+            &[9],
+            signers,
+        )
+    }
+}
+
+/// Creates an associated token account for `wallet` and `mint` if it does not exist yet.
+///
+/// ### Accounts:
+///   0. `[WRITE, SIGNER]` Funding account
+///   1. `[WRITE]` Associated token account address to be created
+///   2. `[]` Wallet address for the new associated token account
+///   3. `[]` The token mint for the new associated token account
+///   4. `[]` System program
+///   5. `[]` SPL Token or Token-2022 program
+pub struct CreateAssociatedTokenAccountIdempotent<'a> {
+    /// Funding account.
+    pub funding_account: &'a AccountInfo,
+    /// Associated token account.
+    pub account: &'a AccountInfo,
+    /// Wallet owning the associated token account.
+    pub wallet: &'a AccountInfo,
+    /// Mint Account.
+    pub mint: &'a AccountInfo,
+    /// System Program.
+    pub system_program: &'a AccountInfo,
+    /// Token Program.
+    pub token_program: &'a AccountInfo,
+}
+
+impl CreateAssociatedTokenAccountIdempotent<'_> {
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        // account metadata
+        let account_metas: [AccountMeta; 6] = [
+            AccountMeta::writable_signer(self.funding_account.key()),
+            AccountMeta::writable(self.account.key()),
+            AccountMeta::readonly(self.wallet.key()),
+            AccountMeta::readonly(self.mint.key()),
+            AccountMeta::readonly(self.system_program.key()),
+            AccountMeta::readonly(self.token_program.key()),
+        ];
+
+        // create idempotent instruction has a '1' discriminator
+        let instruction = Instruction {
+            program_id: &ASSOCIATED_TOKEN_PROGRAM_ID,
+            accounts: &account_metas,
+            data: &[1],
+        };
+
+        invoke_signed(
+            &instruction,
+            &[
+                self.funding_account,
+                self.account,
+                self.wallet,
+                self.mint,
+                self.system_program,
+                self.token_program,
+            ],
+            signers,
+        )
+    }
+}
@@ -0,0 +1,165 @@
+use bytemuck::Zeroable;
+use sol_ver::error::SolverError;
+use sol_ver::state::curve::{AuctionCurve, Breakpoint};
+use sol_ver::state::order::{Order, OrderType};
+
+fn breakpoint(time_offset: u64, buy_amount: u64) -> Breakpoint {
+    Breakpoint {
+        time_offset,
+        buy_amount,
+    }
+}
+
+#[test]
+fn test_exponential_curve() {
+    let order = Order {
+        buy_amount: 1000,
+        minimun_buy_amount: 200,
+        start_time: 100,
+        deadline: 10_000,
+        curve: AuctionCurve::Exponential as u8,
+        curve_period: 100, // Half-life
+        ..Zeroable::zeroed()
+    };
+    assert_eq!(order.calculate_current_buy_amount(100).unwrap(), 1000);
+    // A quarter and a half of the first half-life: 800 * 2^(-1/4), 800 * 2^(-1/2)
+    assert_eq!(order.calculate_current_buy_amount(125).unwrap(), 872);
+    assert_eq!(order.calculate_current_buy_amount(150).unwrap(), 765);
+    // Whole half-lives halve the distance to the floor
+    assert_eq!(order.calculate_current_buy_amount(200).unwrap(), 600);
+    assert_eq!(order.calculate_current_buy_amount(300).unwrap(), 400);
+    // Converges to the floor
+    assert_eq!(order.calculate_current_buy_amount(9_999).unwrap(), 200);
+}
+
+#[test]
+fn test_stepwise_curve() {
+    let order = Order {
+        buy_amount: 1000,
+        minimun_buy_amount: 0,
+        start_time: 0,
+        deadline: 100,
+        curve: AuctionCurve::Stepwise as u8,
+        curve_period: 25, // Step length
+        ..Zeroable::zeroed()
+    };
+    assert_eq!(order.calculate_current_buy_amount(10).unwrap(), 1000);
+    assert_eq!(order.calculate_current_buy_amount(25).unwrap(), 750);
+    assert_eq!(order.calculate_current_buy_amount(49).unwrap(), 750);
+    assert_eq!(order.calculate_current_buy_amount(99).unwrap(), 250);
+    assert_eq!(order.calculate_current_buy_amount(100).unwrap(), 0);
+}
+
+#[test]
+fn test_piecewise_linear_curve() {
+    let mut order = Order {
+        buy_amount: 1000,
+        minimun_buy_amount: 100,
+        start_time: 0,
+        deadline: 100,
+        curve: AuctionCurve::PiecewiseLinear as u8,
+        breakpoint_count: 2,
+        ..Zeroable::zeroed()
+    };
+    order.breakpoints[0] = breakpoint(10, 500);
+    order.breakpoints[1] = breakpoint(50, 400);
+
+    // A steep drop to the first breakpoint
+    assert_eq!(order.calculate_current_buy_amount(5).unwrap(), 750);
+    assert_eq!(order.calculate_current_buy_amount(10).unwrap(), 500);
+    // A slow drift to the second one
+    assert_eq!(order.calculate_current_buy_amount(30).unwrap(), 450);
+    // Then down to the floor at the deadline
+    assert_eq!(order.calculate_current_buy_amount(75).unwrap(), 250);
+    assert_eq!(order.calculate_current_buy_amount(100).unwrap(), 100);
+}
+
+#[test]
+fn test_curve_rejects_invalid_parameters() {
+    let mut piecewise = Order {
+        buy_amount: 1000,
+        minimun_buy_amount: 100,
+        start_time: 0,
+        deadline: 100,
+        curve: AuctionCurve::PiecewiseLinear as u8,
+        breakpoint_count: 2,
+        ..Zeroable::zeroed()
+    };
+    piecewise.breakpoints[0] = breakpoint(10, 500);
+    piecewise.breakpoints[1] = breakpoint(50, 400);
+    assert!(piecewise.check_curve_parameters().is_ok());
+
+    let invalid_curve_parameters = Err(SolverError::InvalidCurveParameters.into());
+
+    // Breakpoints must move forward in time
+    let mut order = piecewise;
+    order.breakpoints[1].time_offset = 10;
+    assert_eq!(order.check_curve_parameters(), invalid_curve_parameters);
+
+    // and can't raise the price
+    let mut order = piecewise;
+    order.breakpoints[1].buy_amount = 600;
+    assert_eq!(order.check_curve_parameters(), invalid_curve_parameters);
+
+    // and must fall before the deadline
+    let mut order = piecewise;
+    order.breakpoints[1].time_offset = 100;
+    assert_eq!(order.check_curve_parameters(), invalid_curve_parameters);
+
+    let order = Order {
+        breakpoint_count: 5,
+        ..piecewise
+    };
+    assert_eq!(order.check_curve_parameters(), invalid_curve_parameters);
+
+    // Breakpoints are meaningless for other curves
+    let order = Order {
+        curve: AuctionCurve::Linear as u8,
+        ..piecewise
+    };
+    assert_eq!(order.check_curve_parameters(), invalid_curve_parameters);
+
+    // Exponential and stepwise curves need a period
+    let order = Order {
+        curve: AuctionCurve::Exponential as u8,
+        breakpoint_count: 0,
+        ..piecewise
+    };
+    assert_eq!(order.check_curve_parameters(), invalid_curve_parameters);
+}
+
+#[test]
+fn test_limit_order_price() {
+    let order = Order {
+        sell_amount: 10,
+        buy_amount: 1000,
+        start_time: 100,
+        deadline: 200,
+        order_type: OrderType::Limit as u8,
+        ..Zeroable::zeroed()
+    };
+    // Exactly buy_amount, whenever it is filled
+    assert_eq!(order.calculate_current_buy_amount(100).unwrap(), 1000);
+    assert_eq!(order.calculate_current_buy_amount(199).unwrap(), 1000);
+    assert_eq!(order.required_buy_amount(150, 1).unwrap(), 100);
+
+    // A limit order carries no auction
+    let order = Order {
+        minimun_buy_amount: 900,
+        ..order
+    };
+    assert_eq!(
+        order.calculate_current_buy_amount(150),
+        Err(SolverError::InvalidCurveParameters.into())
+    );
+
+    let order = Order {
+        minimun_buy_amount: 0,
+        order_type: 2,
+        ..order
+    };
+    assert_eq!(
+        order.check_price_parameters(),
+        Err(SolverError::InvalidOrderType.into())
+    );
+}
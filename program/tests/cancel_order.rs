@@ -0,0 +1,287 @@
+mod common;
+
+use bytemuck::Zeroable;
+use common::*;
+use light_hasher::{Hasher, Keccak};
+use sol_ver::error::SolverError;
+use sol_ver::state::order::{Order, OrderMode};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+struct CancelFixture {
+    program_test: ProgramTest,
+    owner: Keypair,
+    order_pda: Pubkey,
+    order_bump: u8,
+    order_bytes: Vec<u8>,
+    from_token_account: Pubkey,
+    vault: Pubkey,
+    mint: Pubkey,
+}
+
+fn setup(mode: OrderMode) -> CancelFixture {
+    let mut program_test = program_test();
+
+    let owner = Keypair::new();
+    let mint = Pubkey::new_unique();
+    let from_token_account = Pubkey::new_unique();
+
+    let order = Order {
+        from_token_account: to_pinocchio(&from_token_account),
+        to_token_account: to_pinocchio(&Pubkey::new_unique()),
+        sell_amount: 100,
+        buy_amount: 100,
+        referral_fee: 0,
+        referral_token_account: to_pinocchio(&Pubkey::new_unique()),
+        minimun_buy_amount: 90,
+        amount_decrease_per_second: 0,
+        start_time: 0,
+        deadline: 1000,
+        mode: mode as u8,
+        ..Zeroable::zeroed()
+    };
+    let order_bytes = order_bytes(&order);
+    let (order_pda, order_bump) = find_order_pda(&owner.pubkey(), &order_bytes);
+
+    program_test.add_account(
+        order_pda,
+        Account {
+            lamports: 1_000_000,
+            data: order_account_data(&order, 0),
+            owner: Pubkey::new_from_array(sol_ver::ID),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(mint, mint_account(&Pubkey::new_unique(), 1000, 6));
+    let vault = find_vault(&order_pda, &mint);
+    match mode {
+        OrderMode::Escrow => {
+            program_test.add_account(
+                from_token_account,
+                token_account(&mint, &owner.pubkey(), 900),
+            );
+            program_test.add_account(vault, token_account(&mint, &order_pda, 100));
+        }
+        OrderMode::Delegate => {
+            program_test.add_account(
+                from_token_account,
+                token_account_with_delegate(&mint, &owner.pubkey(), 1000, Some((&order_pda, 100))),
+            );
+        }
+    }
+
+    CancelFixture {
+        program_test,
+        owner,
+        order_pda,
+        order_bump,
+        order_bytes,
+        from_token_account,
+        vault,
+        mint,
+    }
+}
+
+fn cancel_instruction(
+    owner: &Pubkey,
+    order_pda: &Pubkey,
+    from_token_account: &Pubkey,
+    vault: &Pubkey,
+    mint: &Pubkey,
+    order_bump: u8,
+    order_bytes: &[u8],
+) -> Instruction {
+    let mut data = vec![1]; // Instruction::Cancel discriminator
+    data.push(order_bump);
+    data.extend_from_slice(order_bytes);
+
+    Instruction {
+        program_id: Pubkey::new_from_array(sol_ver::ID),
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(*order_pda, false),
+            AccountMeta::new(*from_token_account, false),
+            AccountMeta::new(*vault, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*mint, false),
+        ],
+        data,
+    }
+}
+
+async fn cancel(fixture: CancelFixture) -> BanksClient {
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let instruction = cancel_instruction(
+        &fixture.owner.pubkey(),
+        &fixture.order_pda,
+        &fixture.from_token_account,
+        &fixture.vault,
+        &fixture.mint,
+        fixture.order_bump,
+        &fixture.order_bytes,
+    );
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.owner], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    banks_client
+}
+
+#[tokio::test]
+async fn test_cancel_escrow_order() {
+    let fixture = setup(OrderMode::Escrow);
+    let (owner, order_pda, vault, from_token_account) = (
+        fixture.owner.pubkey(),
+        fixture.order_pda,
+        fixture.vault,
+        fixture.from_token_account,
+    );
+    let mut banks_client = cancel(fixture).await;
+
+    // The order account and the vault are closed and their rent refunded to the owner
+    assert!(banks_client.get_account(order_pda).await.unwrap().is_none());
+    assert!(banks_client.get_account(vault).await.unwrap().is_none());
+    let owner_account = banks_client.get_account(owner).await.unwrap().unwrap();
+    assert_eq!(owner_account.lamports, 1_000_000 + 1_000_000_000);
+
+    // The escrowed sell tokens are returned
+    let from_account = banks_client
+        .get_account(from_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(token_amount(&from_account.data), 1000);
+}
+
+#[tokio::test]
+async fn test_cancel_delegate_order() {
+    let fixture = setup(OrderMode::Delegate);
+    let (owner, order_pda, from_token_account) = (
+        fixture.owner.pubkey(),
+        fixture.order_pda,
+        fixture.from_token_account,
+    );
+    let mut banks_client = cancel(fixture).await;
+
+    // The order account is closed and its rent refunded to the owner
+    assert!(banks_client.get_account(order_pda).await.unwrap().is_none());
+    let owner_account = banks_client.get_account(owner).await.unwrap().unwrap();
+    assert_eq!(owner_account.lamports, 1_000_000);
+
+    // The delegation held by the order PDA is revoked
+    let from_account = banks_client
+        .get_account(from_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(token_delegate(&from_account.data), None);
+    assert_eq!(token_amount(&from_account.data), 1000);
+}
+
+#[tokio::test]
+async fn test_cancel_order_while_paused() {
+    let mut fixture = setup(OrderMode::Escrow);
+    fixture.program_test.add_account(
+        find_config_pda().0,
+        config_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 0, true),
+    );
+    let (order_pda, from_token_account) = (fixture.order_pda, fixture.from_token_account);
+    let mut banks_client = cancel(fixture).await;
+
+    // Pausing never locks owners out of their funds
+    assert!(banks_client.get_account(order_pda).await.unwrap().is_none());
+    let from_account = banks_client
+        .get_account(from_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(token_amount(&from_account.data), 1000);
+}
+
+#[tokio::test]
+async fn test_cancel_order_rejects_non_owner() {
+    let fixture = setup(OrderMode::Escrow);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    // An attacker whose key still derives an address with the order's bump
+    let intent_hash = Keccak::hashv(&[&fixture.order_bytes]).unwrap();
+    let attacker = std::iter::repeat_with(Keypair::new)
+        .find(|attacker| {
+            Pubkey::create_program_address(
+                &[
+                    b"order",
+                    attacker.pubkey().as_ref(),
+                    intent_hash.as_ref(),
+                    &[fixture.order_bump],
+                ],
+                &Pubkey::new_from_array(sol_ver::ID),
+            )
+            .is_ok()
+        })
+        .unwrap();
+    let instruction = cancel_instruction(
+        &attacker.pubkey(),
+        &fixture.order_pda,
+        &fixture.from_token_account,
+        &fixture.vault,
+        &fixture.mint,
+        fixture.order_bump,
+        &fixture.order_bytes,
+    );
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &attacker], recent_blockhash);
+
+    // The PDA does not derive from the attacker's key, so the cancel is refused
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidOrderAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_cancel_order_rejects_read_only_order_account() {
+    let fixture = setup(OrderMode::Escrow);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut instruction = cancel_instruction(
+        &fixture.owner.pubkey(),
+        &fixture.order_pda,
+        &fixture.from_token_account,
+        &fixture.vault,
+        &fixture.mint,
+        fixture.order_bump,
+        &fixture.order_bytes,
+    );
+    instruction.accounts[1].is_writable = false;
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.owner], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::OrderAccountMustBeMut as u32)
+        )
+    );
+}
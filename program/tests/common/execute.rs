@@ -0,0 +1,410 @@
+use super::*;
+use sol_ver::state::order::OrderMode;
+use solana_program_test::BanksClient;
+use solana_sdk::{
+    instruction::AccountMeta,
+    transaction::{Transaction, TransactionError},
+};
+
+/// Index of the first remaining account of an Execute instruction.
+pub const REMAINING_ACCOUNTS: usize = 15;
+
+/// How the mints and token accounts of an [`ExecuteFixture`] are laid out.
+pub struct ExecuteSetup {
+    /// Program owning both mints and every token account.
+    pub token_program: Pubkey,
+    /// Token-2022 transfer fee charged on the buy mint.
+    pub buy_transfer_fee_bps: u16,
+    /// Token-2022 transfer hook program of the sell mint.
+    pub sell_transfer_hook: Option<Pubkey>,
+    pub protocol_fee_bps: u64,
+    pub is_paused: bool,
+    pub sell_decimals: u8,
+    pub buy_decimals: u8,
+}
+
+impl Default for ExecuteSetup {
+    fn default() -> Self {
+        Self {
+            token_program: TOKEN_PROGRAM_ID,
+            buy_transfer_fee_bps: 0,
+            sell_transfer_hook: None,
+            protocol_fee_bps: 0,
+            is_paused: false,
+            sell_decimals: 6,
+            buy_decimals: 6,
+        }
+    }
+}
+
+impl ExecuteSetup {
+    fn mint(&self, decimals: u8, transfer_fee_bps: u16, transfer_hook: Option<&Pubkey>) -> Account {
+        if self.token_program == TOKEN_PROGRAM_ID {
+            return mint_account(&Pubkey::new_unique(), 10_000, decimals);
+        }
+
+        let mint = token_2022_mint_account(decimals, transfer_fee_bps, 1_000);
+        match transfer_hook {
+            Some(hook_program) => with_transfer_hook(mint, hook_program),
+            None => mint,
+        }
+    }
+
+    fn token_account(
+        &self,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+        delegate: Option<(&Pubkey, u64)>,
+        is_hooked: bool,
+    ) -> Account {
+        if self.token_program == TOKEN_PROGRAM_ID {
+            return token_account_with_delegate(mint, owner, amount, delegate);
+        }
+
+        let account = token_2022_account(mint, owner, amount, delegate);
+        if is_hooked {
+            with_transfer_hook_account(account)
+        } else {
+            account
+        }
+    }
+}
+
+/// Accounts of an order selling `sell_mint` for `buy_mint`, with a solver holding
+/// enough buy tokens to fill it.
+pub struct ExecuteFixture {
+    pub program_test: ProgramTest,
+    pub token_program: Pubkey,
+    pub owner: Keypair,
+    pub solver: Keypair,
+    pub order: Order,
+    pub order_pda: Pubkey,
+    pub order_bump: u8,
+    pub sell_mint: Pubkey,
+    pub buy_mint: Pubkey,
+    pub from_token_account: Pubkey,
+    pub to_token_account: Pubkey,
+    pub referral_token_account: Pubkey,
+    pub solver_sell_token_account: Pubkey,
+    pub solver_buy_token_account: Pubkey,
+    pub vault: Pubkey,
+    pub config: Pubkey,
+    pub treasury_token_account: Pubkey,
+    /// Account the route delivers the buy tokens to: `to_token_account`, or for a native
+    /// buy the order's wSOL account unwrapped into the owner's wallet.
+    pub route_destination: Pubkey,
+    /// Accounts the sell mint's transfer hook needs, passed ahead of the route accounts.
+    pub hook_accounts: Vec<AccountMeta>,
+}
+
+pub fn setup_execute(customize: impl FnOnce(&mut Order)) -> ExecuteFixture {
+    setup_execute_with(ExecuteSetup::default(), customize)
+}
+
+pub fn setup_execute_with_config(
+    customize: impl FnOnce(&mut Order),
+    protocol_fee_bps: u64,
+    is_paused: bool,
+) -> ExecuteFixture {
+    let setup = ExecuteSetup {
+        protocol_fee_bps,
+        is_paused,
+        ..ExecuteSetup::default()
+    };
+    setup_execute_with(setup, customize)
+}
+
+pub fn setup_execute_with(
+    setup: ExecuteSetup,
+    customize: impl FnOnce(&mut Order),
+) -> ExecuteFixture {
+    let mut program_test = program_test();
+    let token_program = setup.token_program;
+
+    let owner = Keypair::new();
+    let solver = Keypair::new();
+    let sell_mint = Pubkey::new_unique();
+    let from_token_account = Pubkey::new_unique();
+    let referral_token_account = Pubkey::new_unique();
+    let solver_sell_token_account = Pubkey::new_unique();
+    let solver_buy_token_account = Pubkey::new_unique();
+    let treasury = Pubkey::new_unique();
+    let treasury_token_account = Pubkey::new_unique();
+    let (config, _) = find_config_pda();
+
+    let mut order = Order {
+        from_token_account: to_pinocchio(&from_token_account),
+        to_token_account: to_pinocchio(&Pubkey::new_unique()),
+        sell_mint: to_pinocchio(&sell_mint),
+        buy_mint: to_pinocchio(&Pubkey::new_unique()),
+        sell_amount: 100,
+        buy_amount: 100,
+        referral_fee: 0,
+        referral_token_account: to_pinocchio(&referral_token_account),
+        minimun_buy_amount: 90,
+        amount_decrease_per_second: 0,
+        start_time: 0,
+        deadline: u64::MAX,
+        ..Zeroable::zeroed()
+    };
+    customize(&mut order);
+
+    // A native buy delivers SOL straight to the owner's wallet
+    let is_native_buy = order.native_buy != 0;
+    if is_native_buy {
+        order.buy_mint = to_pinocchio(&NATIVE_MINT);
+        order.to_token_account = to_pinocchio(&owner.pubkey());
+    }
+    let buy_mint = Pubkey::new_from_array(order.buy_mint);
+    let to_token_account = Pubkey::new_from_array(order.to_token_account);
+
+    let order_bytes = order_bytes(&order);
+    let (order_pda, order_bump) = find_order_pda(&owner.pubkey(), &order_bytes);
+    let vault = find_vault_with_program(&order_pda, &sell_mint, &token_program);
+
+    program_test.add_account(
+        order_pda,
+        Account {
+            lamports: 1_000_000,
+            data: order_account_data(&order, 0),
+            owner: Pubkey::new_from_array(sol_ver::ID),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(
+        sell_mint,
+        setup.mint(setup.sell_decimals, 0, setup.sell_transfer_hook.as_ref()),
+    );
+    if is_native_buy {
+        program_test.add_account(buy_mint, native_mint_account());
+    } else {
+        program_test.add_account(
+            buy_mint,
+            setup.mint(setup.buy_decimals, setup.buy_transfer_fee_bps, None),
+        );
+    }
+
+    let is_hooked = setup.sell_transfer_hook.is_some();
+    let sell_account = |owner: &Pubkey, amount: u64, delegate: Option<(&Pubkey, u64)>| {
+        setup.token_account(&sell_mint, owner, amount, delegate, is_hooked)
+    };
+    if order.mode == OrderMode::Delegate as u8 {
+        program_test.add_account(
+            from_token_account,
+            sell_account(&owner.pubkey(), 1000, Some((&order_pda, order.sell_amount))),
+        );
+    } else {
+        program_test.add_account(
+            from_token_account,
+            sell_account(&owner.pubkey(), 1000, None),
+        );
+        program_test.add_account(vault, sell_account(&order_pda, order.sell_amount, None));
+    }
+    let route_destination = if is_native_buy {
+        // The solver opens the unwrap account ahead of the fill
+        let unwrap_account = find_vault(&order_pda, &NATIVE_MINT);
+        program_test.add_account(unwrap_account, native_token_account(&order_pda, 0));
+        program_test.add_account(
+            owner.pubkey(),
+            Account {
+                lamports: 1_000_000_000,
+                ..Account::default()
+            },
+        );
+        unwrap_account
+    } else {
+        program_test.add_account(
+            to_token_account,
+            setup.token_account(&buy_mint, &owner.pubkey(), 0, None, false),
+        );
+        to_token_account
+    };
+    program_test.add_account(
+        referral_token_account,
+        sell_account(&Pubkey::new_unique(), 0, None),
+    );
+    program_test.add_account(
+        solver_sell_token_account,
+        sell_account(&solver.pubkey(), 0, None),
+    );
+    if is_native_buy {
+        program_test.add_account(
+            solver_buy_token_account,
+            native_token_account(&solver.pubkey(), 1000),
+        );
+    } else {
+        program_test.add_account(
+            solver_buy_token_account,
+            setup.token_account(&buy_mint, &solver.pubkey(), 1000, None, false),
+        );
+    }
+    program_test.add_account(
+        config,
+        config_account(
+            &Pubkey::new_unique(),
+            &treasury,
+            setup.protocol_fee_bps,
+            setup.is_paused,
+        ),
+    );
+    program_test.add_account(treasury_token_account, sell_account(&treasury, 0, None));
+    program_test.add_account(
+        find_allow_list_pda().0,
+        allow_list_account(&[token_program, TOKEN_PROGRAM_ID]),
+    );
+    program_test.add_account(
+        solver.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            ..Account::default()
+        },
+    );
+
+    let mut hook_accounts = Vec::new();
+    if let Some(hook_program) = setup.sell_transfer_hook {
+        let validation = find_extra_account_metas_pda(&sell_mint, &hook_program);
+        // The hook has no SBF build, it always runs natively
+        program_test.prefer_bpf(false);
+        program_test.add_program(
+            "transfer_hook",
+            hook_program,
+            processor!(accept_transfer_hook),
+        );
+        program_test.prefer_bpf(true);
+        program_test.add_account(validation, extra_account_metas_account(&hook_program));
+        hook_accounts = vec![
+            AccountMeta::new_readonly(validation, false),
+            AccountMeta::new_readonly(hook_program, false),
+        ];
+    }
+
+    ExecuteFixture {
+        program_test,
+        token_program,
+        owner,
+        solver,
+        order,
+        order_pda,
+        order_bump,
+        sell_mint,
+        buy_mint,
+        from_token_account,
+        to_token_account,
+        referral_token_account,
+        solver_sell_token_account,
+        solver_buy_token_account,
+        vault,
+        config,
+        treasury_token_account,
+        route_destination,
+        hook_accounts,
+    }
+}
+
+/// Builds an Execute instruction filling the whole order, whose route transfers
+/// `route_amount` buy tokens from the solver to the owner.
+pub fn execute_instruction(fixture: &ExecuteFixture, route_amount: u64) -> Instruction {
+    partial_execute_instruction(fixture, fixture.order.sell_amount, route_amount)
+}
+
+/// Builds an Execute instruction filling `fill_amount` sell tokens, whose route transfers
+/// `route_amount` buy tokens from the solver to the owner.
+pub fn partial_execute_instruction(
+    fixture: &ExecuteFixture,
+    fill_amount: u64,
+    route_amount: u64,
+) -> Instruction {
+    let hooks = fixture.hook_accounts.len() as u8;
+    // wSOL is an SPL Token mint, whatever program the sell mint belongs to
+    let buy_token_program = if fixture.order.native_buy != 0 {
+        TOKEN_PROGRAM_ID
+    } else {
+        fixture.token_program
+    };
+    let route = if buy_token_program == TOKEN_PROGRAM_ID {
+        route_data_with_hooks(
+            hooks,
+            &[(hooks, hooks + 1, 3, &token_transfer_data(route_amount))],
+        )
+    } else {
+        route_data_with_hooks(
+            hooks,
+            &[(hooks, hooks + 1, 4, &transfer_checked_data(route_amount))],
+        )
+    };
+
+    let mut data = vec![2]; // Instruction::Execute discriminator
+    data.push(fixture.order_bump);
+    data.extend_from_slice(&order_bytes(&fixture.order));
+    data.extend_from_slice(&fill_amount.to_le_bytes());
+    data.extend_from_slice(&route);
+
+    let mut accounts = vec![
+        AccountMeta::new(fixture.solver.pubkey(), true),
+        AccountMeta::new(fixture.order_pda, false),
+        AccountMeta::new(fixture.owner.pubkey(), false),
+        AccountMeta::new(fixture.from_token_account, false),
+        AccountMeta::new(fixture.to_token_account, false),
+        AccountMeta::new(fixture.referral_token_account, false),
+        AccountMeta::new_readonly(fixture.token_program, false),
+        AccountMeta::new_readonly(fixture.sell_mint, false),
+        AccountMeta::new(fixture.vault, false),
+        AccountMeta::new(fixture.solver_sell_token_account, false),
+        AccountMeta::new_readonly(fixture.config, false),
+        AccountMeta::new(fixture.treasury_token_account, false),
+        AccountMeta::new(
+            find_nonce_pda(&fixture.owner.pubkey(), fixture.order.nonce),
+            false,
+        ),
+        AccountMeta::new_readonly(Pubkey::default(), false), // System Program
+        AccountMeta::new_readonly(find_allow_list_pda().0, false),
+    ];
+    accounts.extend_from_slice(&fixture.hook_accounts);
+    // Remaining Accounts for the route: program, source, (mint,) destination, authority
+    accounts.push(AccountMeta::new_readonly(buy_token_program, false));
+    accounts.push(AccountMeta::new(fixture.solver_buy_token_account, false));
+    if buy_token_program != TOKEN_PROGRAM_ID {
+        accounts.push(AccountMeta::new_readonly(fixture.buy_mint, false));
+    }
+    accounts.push(AccountMeta::new(fixture.route_destination, false));
+    accounts.push(AccountMeta::new_readonly(fixture.solver.pubkey(), true));
+
+    Instruction {
+        program_id: Pubkey::new_from_array(sol_ver::ID),
+        accounts,
+        data,
+    }
+}
+
+/// Runs `instruction`, signed by the fixture's solver, against a fresh fixture.
+pub async fn execute(
+    fixture: ExecuteFixture,
+    instruction: Instruction,
+) -> Result<BanksClient, TransactionError> {
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client
+        .process_transaction(tx)
+        .await
+        .map_err(|err| err.unwrap())?;
+
+    Ok(banks_client)
+}
+
+/// Runs `instruction` against a fresh fixture, expecting it to fail.
+pub async fn execute_err(fixture: ExecuteFixture, instruction: Instruction) -> TransactionError {
+    execute(fixture, instruction).await.err().unwrap()
+}
+
+pub async fn token_balance(banks_client: &mut BanksClient, token_account: Pubkey) -> u64 {
+    let account = banks_client
+        .get_account(token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    token_amount(&account.data)
+}
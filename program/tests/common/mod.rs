@@ -0,0 +1,526 @@
+#![allow(dead_code)]
+
+mod execute;
+
+#[allow(unused_imports)]
+pub use execute::*;
+
+use bytemuck::Zeroable;
+use light_hasher::{Hasher, Keccak};
+use sol_ver::state::{
+    allow_list::AllowList,
+    config::Config,
+    nonce::NonceBitmap,
+    oracle::{PRICE_UPDATE_V2_DISCRIMINATOR, PYTH_RECEIVER_ID},
+    order::{Order, OrderAccount},
+};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    instruction::Instruction,
+    signature::{Keypair, Signer},
+};
+
+pub const TOKEN_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+
+pub const TOKEN_2022_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    solana_sdk::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+pub const NATIVE_MINT: Pubkey = solana_sdk::pubkey!("So11111111111111111111111111111111111111112");
+
+/// Rent-exempt reserve of a 165-byte SPL Token account.
+pub const TOKEN_ACCOUNT_RENT: u64 = 2_039_280;
+
+pub fn program_test() -> ProgramTest {
+    let program_id = Pubkey::new_from_array(sol_ver::ID);
+    let mut program_test = ProgramTest::new("sol_ver", program_id, processor!(sol_ver_adapter));
+    program_test.prefer_bpf(true);
+    program_test
+}
+
+pub fn to_pinocchio(pubkey: &Pubkey) -> pinocchio::pubkey::Pubkey {
+    pinocchio::pubkey::Pubkey::from(pubkey.to_bytes())
+}
+
+pub fn order_bytes(order: &Order) -> Vec<u8> {
+    bytemuck::bytes_of(order).to_vec()
+}
+
+/// Data of an initialized order account with `filled_sell_amount` already filled.
+pub fn order_account_data(order: &Order, filled_sell_amount: u64) -> Vec<u8> {
+    bytemuck::bytes_of(&OrderAccount {
+        order: *order,
+        filled_sell_amount,
+    })
+    .to_vec()
+}
+
+/// Derives the order PDA and bump for `owner` and the serialized order.
+pub fn find_order_pda(owner: &Pubkey, order_bytes: &[u8]) -> (Pubkey, u8) {
+    let intent_hash = Keccak::hashv(&[order_bytes]).unwrap();
+    Pubkey::find_program_address(
+        &[b"order", owner.as_ref(), intent_hash.as_ref()],
+        &Pubkey::new_from_array(sol_ver::ID),
+    )
+}
+
+/// A bump for which `seeds` derive no program address, the hash landing on the curve.
+pub fn find_invalid_bump(seeds: &[&[u8]]) -> u8 {
+    let program_id = Pubkey::new_from_array(sol_ver::ID);
+    (0..=u8::MAX)
+        .find(|bump| {
+            let bump_seed = [*bump];
+            let mut seeds = seeds.to_vec();
+            seeds.push(&bump_seed);
+            Pubkey::create_program_address(&seeds, &program_id).is_err()
+        })
+        .unwrap()
+}
+
+/// Derives the escrow vault: the associated token account of the order PDA for `mint`.
+pub fn find_vault(order_pda: &Pubkey, mint: &Pubkey) -> Pubkey {
+    find_vault_with_program(order_pda, mint, &TOKEN_PROGRAM_ID)
+}
+
+/// Derives the escrow vault of a `mint` owned by `token_program`.
+pub fn find_vault_with_program(
+    order_pda: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[order_pda.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Derives the authority PDA executing the off-chain signed orders of `owner`.
+pub fn find_authority_pda(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"authority", owner.as_ref()],
+        &Pubkey::new_from_array(sol_ver::ID),
+    )
+}
+
+/// An Ed25519 precompile instruction verifying `signer`'s signature over `message`,
+/// with the public key, signature and message all stored in the instruction itself.
+pub fn ed25519_instruction(signer: &Keypair, message: &[u8]) -> Instruction {
+    const DATA_START: u16 = 16; // 2 bytes header + 14 bytes offsets
+    let public_key_offset = DATA_START;
+    let signature_offset = public_key_offset + 32;
+    let message_offset = signature_offset + 64;
+
+    let mut data = vec![1, 0]; // One signature, padding
+    for value in [
+        signature_offset,
+        u16::MAX,
+        public_key_offset,
+        u16::MAX,
+        message_offset,
+        message.len() as u16,
+        u16::MAX,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signer.sign_message(message).as_ref());
+    data.extend_from_slice(message);
+
+    Instruction {
+        program_id: solana_sdk::ed25519_program::id(),
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Derives the filled-nonces bitmap account of `owner` covering `nonce`.
+pub fn find_nonce_pda(owner: &Pubkey, nonce: u64) -> Pubkey {
+    let word = nonce / NonceBitmap::NONCES_PER_ACCOUNT;
+    Pubkey::find_program_address(
+        &[b"nonces", owner.as_ref(), &word.to_le_bytes()],
+        &Pubkey::new_from_array(sol_ver::ID),
+    )
+    .0
+}
+
+/// A valid off-curve address for `seeds` under a bump other than the canonical one.
+pub fn find_non_canonical_pda(seeds: &[&[u8]]) -> (Pubkey, u8) {
+    let program_id = Pubkey::new_from_array(sol_ver::ID);
+    let (_, canonical_bump) = Pubkey::find_program_address(seeds, &program_id);
+    (0..canonical_bump)
+        .rev()
+        .find_map(|bump| {
+            let bump_seed = [bump];
+            let mut seeds = seeds.to_vec();
+            seeds.push(&bump_seed);
+            Pubkey::create_program_address(&seeds, &program_id)
+                .ok()
+                .map(|address| (address, bump))
+        })
+        .unwrap()
+}
+
+pub fn find_config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"config"], &Pubkey::new_from_array(sol_ver::ID))
+}
+
+/// Encodes Execute route data from `(program_index, accounts_start, accounts_len, data)`
+/// hops, indices relative to the remaining accounts.
+pub fn route_data(hops: &[(u8, u8, u8, &[u8])]) -> Vec<u8> {
+    route_data_with_hooks(0, hops)
+}
+
+/// Route data whose first `hook_accounts` remaining accounts go to the sell-side transfers.
+pub fn route_data_with_hooks(hook_accounts: u8, hops: &[(u8, u8, u8, &[u8])]) -> Vec<u8> {
+    let mut data = vec![hook_accounts, hops.len() as u8];
+    for (program_index, accounts_start, accounts_len, hop_data) in hops {
+        data.extend_from_slice(&[*program_index, *accounts_start, *accounts_len]);
+        data.extend_from_slice(&(hop_data.len() as u16).to_le_bytes());
+        data.extend_from_slice(hop_data);
+    }
+    data
+}
+
+/// Instruction data of an SPL Token `Transfer` of `amount`.
+pub fn token_transfer_data(amount: u64) -> Vec<u8> {
+    let mut data = vec![3]; // spl_token Transfer
+    data.extend_from_slice(&amount.to_le_bytes());
+    data
+}
+
+/// A single-hop route transferring `amount` tokens, for remaining accounts laid out as
+/// token program, source, destination, authority.
+pub fn transfer_route(amount: u64) -> Vec<u8> {
+    route_data(&[(0, 1, 3, &token_transfer_data(amount))])
+}
+
+pub fn find_allow_list_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"allow_list"], &Pubkey::new_from_array(sol_ver::ID))
+}
+
+/// An initialized allow-list PDA account permitting `programs` as route targets.
+pub fn allow_list_account(programs: &[Pubkey]) -> Account {
+    let mut allow_list = AllowList::zeroed();
+    for (slot, program) in allow_list.programs.iter_mut().zip(programs) {
+        *slot = to_pinocchio(program);
+    }
+    allow_list.count = programs.len() as u8;
+    allow_list.is_initialized = 1;
+    allow_list.bump = find_allow_list_pda().1;
+
+    Account {
+        lamports: 1_000_000_000,
+        data: bytemuck::bytes_of(&allow_list).to_vec(),
+        owner: Pubkey::new_from_array(sol_ver::ID),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// An initialized config PDA account.
+pub fn config_account(
+    admin: &Pubkey,
+    treasury: &Pubkey,
+    protocol_fee_bps: u64,
+    is_paused: bool,
+) -> Account {
+    let config = Config {
+        admin: to_pinocchio(admin),
+        treasury: to_pinocchio(treasury),
+        protocol_fee_bps,
+        is_initialized: 1,
+        is_paused: is_paused as u8,
+        bump: find_config_pda().1,
+        _padding: [0; 5],
+    };
+
+    Account {
+        lamports: 1_000_000_000,
+        data: bytemuck::bytes_of(&config).to_vec(),
+        owner: Pubkey::new_from_array(sol_ver::ID),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+pub const BPF_LOADER_UPGRADEABLE_ID: Pubkey =
+    solana_sdk::pubkey!("BPFLoaderUpgradeab1e11111111111111111111111");
+
+pub fn find_program_data_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[&sol_ver::ID], &BPF_LOADER_UPGRADEABLE_ID)
+}
+
+/// ProgramData account of `sol_ver` with `upgrade_authority` allowed to upgrade it.
+pub fn program_data_account(upgrade_authority: &Pubkey) -> Account {
+    let mut data = vec![3, 0, 0, 0]; // UpgradeableLoaderState::ProgramData
+    data.extend_from_slice(&0u64.to_le_bytes()); // slot
+    data.push(1); // Some(upgrade_authority)
+    data.extend_from_slice(upgrade_authority.as_ref());
+
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: BPF_LOADER_UPGRADEABLE_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Fully verified Pyth `PriceUpdateV2` account quoting `price * 10^-decimals`.
+pub fn oracle_account(price: u64, decimals: u8, publish_time: i64) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data: price_update_data(price as i64, -(decimals as i32), publish_time),
+        owner: Pubkey::new_from_array(PYTH_RECEIVER_ID),
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Pyth feed id of the price updates built by [`price_update_data`].
+pub const ORACLE_FEED_ID: [u8; 32] = [7; 32];
+
+/// Data of a mock oracle price account in the layout read by the oracle guard.
+pub fn price_update_data(price: i64, exponent: i32, publish_time: i64) -> Vec<u8> {
+    let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(Pubkey::new_unique().as_ref()); // write_authority
+    data.push(1); // VerificationLevel::Full
+    data.extend_from_slice(&ORACLE_FEED_ID);
+    data.extend_from_slice(&price.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // conf
+    data.extend_from_slice(&exponent.to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes());
+    data.extend_from_slice(&publish_time.to_le_bytes()); // prev_publish_time
+    data.extend_from_slice(&price.to_le_bytes()); // ema_price
+    data.extend_from_slice(&0u64.to_le_bytes()); // ema_conf
+    data.extend_from_slice(&0u64.to_le_bytes()); // posted_slot
+    data
+}
+
+/// An initialized SPL Token mint with the given supply and decimals.
+pub fn mint_account(mint_authority: &Pubkey, supply: u64, decimals: u8) -> Account {
+    let mut data = vec![0u8; 82]; // Mint::LEN = 82
+    data[0..4].copy_from_slice(&1u32.to_le_bytes()); // Option::Some
+    data[4..36].copy_from_slice(mint_authority.as_ref());
+    data[36..44].copy_from_slice(&supply.to_le_bytes());
+    data[44] = decimals;
+    data[45] = 1; // IsInitialized
+
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: TOKEN_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// An initialized SPL Token account holding `amount` of `mint`.
+pub fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    token_account_with_delegate(mint, owner, amount, None)
+}
+
+/// An initialized SPL Token account with an optional `(delegate, delegated_amount)`.
+pub fn token_account_with_delegate(
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+    delegate: Option<(&Pubkey, u64)>,
+) -> Account {
+    let mut data = vec![0u8; 165]; // TokenAccount::LEN = 165
+    data[0..32].copy_from_slice(mint.as_ref());
+    data[32..64].copy_from_slice(owner.as_ref());
+    data[64..72].copy_from_slice(&amount.to_le_bytes());
+    if let Some((delegate, delegated_amount)) = delegate {
+        data[72..76].copy_from_slice(&1u32.to_le_bytes()); // Option::Some
+        data[76..108].copy_from_slice(delegate.as_ref());
+        data[121..129].copy_from_slice(&delegated_amount.to_le_bytes());
+    }
+    data[108] = 1; // State: Initialized
+
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: TOKEN_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// A wrapped SOL account holding `amount` lamports on top of its rent-exempt reserve.
+/// The wSOL mint, which has no mint authority.
+pub fn native_mint_account() -> Account {
+    mint_account(&Pubkey::default(), 0, 9)
+}
+
+pub fn native_token_account(owner: &Pubkey, amount: u64) -> Account {
+    let mut account = token_account(&NATIVE_MINT, owner, amount);
+    account.data[109..113].copy_from_slice(&1u32.to_le_bytes()); // is_native: Option::Some
+    account.data[113..121].copy_from_slice(&TOKEN_ACCOUNT_RENT.to_le_bytes());
+    account.lamports = TOKEN_ACCOUNT_RENT + amount;
+    account
+}
+
+/// Token-2022 accounts with extensions store their type at this offset, followed by TLV
+/// extension entries.
+const ACCOUNT_TYPE_OFFSET: usize = 165;
+
+/// A Token-2022 mint, with a transfer fee extension charging `transfer_fee_bps` up to
+/// `maximum_fee` when `transfer_fee_bps` is non-zero.
+pub fn token_2022_mint_account(decimals: u8, transfer_fee_bps: u16, maximum_fee: u64) -> Account {
+    let mut data = mint_account(&Pubkey::new_unique(), 10_000, decimals).data;
+    if transfer_fee_bps > 0 {
+        data.resize(ACCOUNT_TYPE_OFFSET, 0);
+        data.push(1); // AccountType::Mint
+        data.extend_from_slice(&1u16.to_le_bytes()); // ExtensionType::TransferFeeConfig
+        data.extend_from_slice(&108u16.to_le_bytes());
+        data.extend_from_slice(&[0u8; 72]); // Authorities and withheld amount
+        for epoch in [0u64, 0] {
+            // Older and newer fee, both in effect from epoch 0
+            data.extend_from_slice(&epoch.to_le_bytes());
+            data.extend_from_slice(&maximum_fee.to_le_bytes());
+            data.extend_from_slice(&transfer_fee_bps.to_le_bytes());
+        }
+    }
+
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: TOKEN_2022_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// A Token-2022 account holding `amount` of `mint` with an optional
+/// `(delegate, delegated_amount)`, and the withheld fee extension accounts of transfer fee
+/// mints require.
+pub fn token_2022_account(
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+    delegate: Option<(&Pubkey, u64)>,
+) -> Account {
+    let mut data = token_account_with_delegate(mint, owner, amount, delegate).data;
+    data.push(2); // AccountType::Account
+    data.extend_from_slice(&2u16.to_le_bytes()); // ExtensionType::TransferFeeAmount
+    data.extend_from_slice(&8u16.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: TOKEN_2022_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Adds a `TransferHook` extension pointing at `hook_program` to a Token-2022 mint.
+pub fn with_transfer_hook(mut mint: Account, hook_program: &Pubkey) -> Account {
+    if mint.data.len() < ACCOUNT_TYPE_OFFSET {
+        mint.data.resize(ACCOUNT_TYPE_OFFSET, 0);
+        mint.data.push(1); // AccountType::Mint
+    }
+    mint.data.extend_from_slice(&14u16.to_le_bytes()); // ExtensionType::TransferHook
+    mint.data.extend_from_slice(&64u16.to_le_bytes());
+    mint.data.extend_from_slice(&[0u8; 32]); // No authority
+    mint.data.extend_from_slice(hook_program.as_ref());
+    mint
+}
+
+/// Adds the `TransferHookAccount` extension Token-2022 flags during hooked transfers.
+pub fn with_transfer_hook_account(mut account: Account) -> Account {
+    account.data.extend_from_slice(&15u16.to_le_bytes()); // ExtensionType::TransferHookAccount
+    account.data.extend_from_slice(&1u16.to_le_bytes());
+    account.data.push(0); // Not transferring
+    account
+}
+
+/// Derives the account listing the extra accounts of `hook_program` for `mint`.
+pub fn find_extra_account_metas_pda(mint: &Pubkey, hook_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"extra-account-metas", mint.as_ref()], hook_program).0
+}
+
+/// Extra account metas list of a hook needing no accounts beyond the validation account.
+pub fn extra_account_metas_account(hook_program: &Pubkey) -> Account {
+    let mut data = TRANSFER_HOOK_EXECUTE_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&4u32.to_le_bytes()); // TLV length
+    data.extend_from_slice(&0u32.to_le_bytes()); // No extra accounts
+
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: *hook_program,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// `sha256("spl-transfer-hook-interface:execute")[..8]`.
+pub const TRANSFER_HOOK_EXECUTE_DISCRIMINATOR: [u8; 8] = [105, 37, 101, 197, 75, 251, 102, 26];
+
+/// Transfer hook accepting every transfer, as long as it's called with `Execute`.
+pub fn accept_transfer_hook(
+    _program_id: &Pubkey,
+    _accounts: &[solana_sdk::account_info::AccountInfo],
+    instruction_data: &[u8],
+) -> solana_program::entrypoint::ProgramResult {
+    if !instruction_data.starts_with(&TRANSFER_HOOK_EXECUTE_DISCRIMINATOR) {
+        return Err(solana_sdk::program_error::ProgramError::InvalidInstructionData);
+    }
+    Ok(())
+}
+
+/// Instruction data of a Token-2022 `TransferChecked` of `amount` with 6 decimals.
+pub fn transfer_checked_data(amount: u64) -> Vec<u8> {
+    let mut data = vec![12]; // spl_token TransferChecked
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.push(6);
+    data
+}
+
+pub fn token_amount(data: &[u8]) -> u64 {
+    u64::from_le_bytes(data[64..72].try_into().unwrap())
+}
+
+pub fn token_delegate(data: &[u8]) -> Option<Pubkey> {
+    if data[72..76] == [0u8; 4] {
+        None
+    } else {
+        Some(Pubkey::new_from_array(data[76..108].try_into().unwrap()))
+    }
+}
+
+pub fn sol_ver_adapter(
+    program_id: &Pubkey,
+    accounts: &[solana_sdk::account_info::AccountInfo],
+    instruction_data: &[u8],
+) -> solana_program::entrypoint::ProgramResult {
+    // Transmute Solana SDK types to Pinocchio types
+    // Pinocchio types are repr(C) compliant with Solana types
+    let p_program_id: &pinocchio::pubkey::Pubkey = unsafe { std::mem::transmute(program_id) };
+    let p_accounts: &[pinocchio::account_info::AccountInfo] =
+        unsafe { std::mem::transmute(accounts) };
+
+    sol_ver::instruction::process_instruction(p_program_id, p_accounts, instruction_data).map_err(
+        |e| match e {
+            pinocchio::program_error::ProgramError::Custom(c) => {
+                solana_sdk::program_error::ProgramError::Custom(c)
+            }
+            pinocchio::program_error::ProgramError::InvalidArgument => {
+                solana_sdk::program_error::ProgramError::InvalidArgument
+            }
+            pinocchio::program_error::ProgramError::InvalidInstructionData => {
+                solana_sdk::program_error::ProgramError::InvalidInstructionData
+            }
+            _ => solana_sdk::program_error::ProgramError::Custom(999),
+        },
+    )
+}
@@ -0,0 +1,452 @@
+mod common;
+
+use common::*;
+use sol_ver::{
+    error::SolverError,
+    instruction::initialize_config::ConfigArgs,
+    state::{allow_list::AllowList, config::Config},
+};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+fn config_args(admin: &Pubkey, treasury: &Pubkey, protocol_fee_bps: u64) -> ConfigArgs {
+    ConfigArgs {
+        admin: to_pinocchio(admin),
+        treasury: to_pinocchio(treasury),
+        protocol_fee_bps,
+    }
+}
+
+fn initialize_config_instruction(payer: &Pubkey, args: &ConfigArgs) -> Instruction {
+    let (config_pda, config_bump) = find_config_pda();
+
+    let mut data = vec![3]; // Instruction::InitializeConfig discriminator
+    data.push(config_bump);
+    data.extend_from_slice(bytemuck::bytes_of(args));
+
+    Instruction {
+        program_id: Pubkey::new_from_array(sol_ver::ID),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(config_pda, false),
+            AccountMeta::new_readonly(Pubkey::default(), false), // System Program
+            AccountMeta::new_readonly(find_program_data_pda().0, false),
+        ],
+        data,
+    }
+}
+
+fn update_config_instruction(admin: &Pubkey, args: &ConfigArgs) -> Instruction {
+    let mut data = vec![4]; // Instruction::UpdateConfig discriminator
+    data.extend_from_slice(bytemuck::bytes_of(args));
+
+    Instruction {
+        program_id: Pubkey::new_from_array(sol_ver::ID),
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(find_config_pda().0, false),
+        ],
+        data,
+    }
+}
+
+fn set_paused_instruction(admin: &Pubkey, is_paused: bool) -> Instruction {
+    Instruction {
+        program_id: Pubkey::new_from_array(sol_ver::ID),
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(find_config_pda().0, false),
+        ],
+        data: vec![5, is_paused as u8], // Instruction::SetPaused discriminator
+    }
+}
+
+fn update_allow_list_instruction(admin: &Pubkey, programs: &[Pubkey]) -> Instruction {
+    let (allow_list_pda, allow_list_bump) = find_allow_list_pda();
+
+    let mut data = vec![7]; // Instruction::UpdateAllowList discriminator
+    data.push(allow_list_bump);
+    for program in programs {
+        data.extend_from_slice(program.as_ref());
+    }
+
+    Instruction {
+        program_id: Pubkey::new_from_array(sol_ver::ID),
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(find_config_pda().0, false),
+            AccountMeta::new(allow_list_pda, false),
+            AccountMeta::new_readonly(Pubkey::default(), false), // System Program
+        ],
+        data,
+    }
+}
+
+async fn read_config(banks_client: &mut BanksClient) -> Config {
+    let account = banks_client
+        .get_account(find_config_pda().0)
+        .await
+        .unwrap()
+        .unwrap();
+    *bytemuck::from_bytes::<Config>(&account.data)
+}
+
+#[tokio::test]
+async fn test_initialize_and_update_config() {
+    let mut program_test = program_test();
+    let upgrade_authority = Keypair::new();
+    program_test.add_account(
+        find_program_data_pda().0,
+        program_data_account(&upgrade_authority.pubkey()),
+    );
+    program_test.add_account(
+        upgrade_authority.pubkey(),
+        solana_sdk::account::Account {
+            lamports: 1_000_000_000,
+            ..Default::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let admin = Keypair::new();
+    let treasury = Pubkey::new_unique();
+
+    // 1. Initialize
+    let instruction = initialize_config_instruction(
+        &upgrade_authority.pubkey(),
+        &config_args(&admin.pubkey(), &treasury, 30),
+    );
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &upgrade_authority], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let config = read_config(&mut banks_client).await;
+    assert_eq!(config.admin, to_pinocchio(&admin.pubkey()));
+    assert_eq!(config.treasury, to_pinocchio(&treasury));
+    assert_eq!(config.protocol_fee_bps, 30);
+    assert_eq!(config.is_initialized, 1);
+    assert_eq!(config.is_paused, 0);
+
+    // 2. Update
+    let new_treasury = Pubkey::new_unique();
+    let instruction = update_config_instruction(
+        &admin.pubkey(),
+        &config_args(&admin.pubkey(), &new_treasury, 50),
+    );
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &admin], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let config = read_config(&mut banks_client).await;
+    assert_eq!(config.treasury, to_pinocchio(&new_treasury));
+    assert_eq!(config.protocol_fee_bps, 50);
+}
+
+#[tokio::test]
+async fn test_initialize_config_rejects_non_upgrade_authority() {
+    let mut program_test = program_test();
+    program_test.add_account(
+        find_program_data_pda().0,
+        program_data_account(&Pubkey::new_unique()),
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // Front-running the deployer to make themselves admin
+    let instruction = initialize_config_instruction(
+        &payer.pubkey(),
+        &config_args(&payer.pubkey(), &payer.pubkey(), 10_000),
+    );
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidAdmin as u32)
+        )
+    );
+    assert!(banks_client
+        .get_account(find_config_pda().0)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_initialize_config_rejects_non_canonical_bump() {
+    let mut program_test = program_test();
+    let upgrade_authority = Keypair::new();
+    program_test.add_account(
+        find_program_data_pda().0,
+        program_data_account(&upgrade_authority.pubkey()),
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // A second config under another bump would let the admin fork the settings
+    let (config_pda, config_bump) = find_non_canonical_pda(&[b"config"]);
+    let mut instruction = initialize_config_instruction(
+        &upgrade_authority.pubkey(),
+        &config_args(&payer.pubkey(), &payer.pubkey(), 30),
+    );
+    instruction.accounts[1].pubkey = config_pda;
+    instruction.data[1] = config_bump;
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &upgrade_authority], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidConfigAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_update_config_rejects_non_canonical_config() {
+    let mut program_test = program_test();
+    let admin = Keypair::new();
+    let (config_pda, config_bump) = find_non_canonical_pda(&[b"config"]);
+    let mut account = config_account(&admin.pubkey(), &Pubkey::new_unique(), 30, false);
+    bytemuck::from_bytes_mut::<Config>(&mut account.data).bump = config_bump;
+    program_test.add_account(config_pda, account);
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let mut instruction = update_config_instruction(
+        &admin.pubkey(),
+        &config_args(&admin.pubkey(), &admin.pubkey(), 50),
+    );
+    instruction.accounts[1].pubkey = config_pda;
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &admin], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidConfigAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_update_config_rejects_non_admin() {
+    let mut program_test = program_test();
+    let admin = Pubkey::new_unique();
+    program_test.add_account(
+        find_config_pda().0,
+        config_account(&admin, &Pubkey::new_unique(), 30, false),
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let attacker = Keypair::new();
+    let instruction = update_config_instruction(
+        &attacker.pubkey(),
+        &config_args(&attacker.pubkey(), &attacker.pubkey(), 10_000),
+    );
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &attacker], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidAdmin as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_set_paused() {
+    let mut program_test = program_test();
+    let admin = Keypair::new();
+    program_test.add_account(
+        find_config_pda().0,
+        config_account(&admin.pubkey(), &Pubkey::new_unique(), 30, false),
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let instruction = set_paused_instruction(&admin.pubkey(), true);
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &admin], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(read_config(&mut banks_client).await.is_paused, 1);
+
+    let instruction = set_paused_instruction(&admin.pubkey(), false);
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &admin], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+    assert_eq!(read_config(&mut banks_client).await.is_paused, 0);
+}
+
+#[tokio::test]
+async fn test_set_paused_rejects_non_admin() {
+    let mut program_test = program_test();
+    program_test.add_account(
+        find_config_pda().0,
+        config_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 30, false),
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let attacker = Keypair::new();
+    let instruction = set_paused_instruction(&attacker.pubkey(), true);
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &attacker], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidAdmin as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_update_allow_list() {
+    let mut program_test = program_test();
+    let admin = Keypair::new();
+    program_test.add_account(
+        find_config_pda().0,
+        config_account(&admin.pubkey(), &Pubkey::new_unique(), 30, false),
+    );
+    program_test.add_account(
+        admin.pubkey(),
+        solana_sdk::account::Account {
+            lamports: 1_000_000_000,
+            ..Default::default()
+        },
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    let amm = Pubkey::new_unique();
+    let read_allow_list = |account: solana_sdk::account::Account| {
+        let allow_list = *bytemuck::from_bytes::<AllowList>(&account.data);
+        allow_list.programs().to_vec()
+    };
+
+    // 1. The first update creates the allow-list
+    let instruction = update_allow_list_instruction(&admin.pubkey(), &[TOKEN_PROGRAM_ID, amm]);
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &admin], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let account = banks_client
+        .get_account(find_allow_list_pda().0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        read_allow_list(account),
+        vec![to_pinocchio(&TOKEN_PROGRAM_ID), to_pinocchio(&amm)]
+    );
+
+    // 2. Later updates replace the whole list
+    let instruction = update_allow_list_instruction(&admin.pubkey(), &[amm]);
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &admin], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let account = banks_client
+        .get_account(find_allow_list_pda().0)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(read_allow_list(account), vec![to_pinocchio(&amm)]);
+}
+
+#[tokio::test]
+async fn test_update_allow_list_rejects_non_admin() {
+    let mut program_test = program_test();
+    program_test.add_account(
+        find_config_pda().0,
+        config_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 30, false),
+    );
+    program_test.add_account(
+        find_allow_list_pda().0,
+        allow_list_account(&[TOKEN_PROGRAM_ID]),
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // An attacker listing their own program to receive the order PDA's authority
+    let attacker = Keypair::new();
+    let instruction = update_allow_list_instruction(&attacker.pubkey(), &[Pubkey::new_unique()]);
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &attacker], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidAdmin as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_update_allow_list_rejects_non_canonical_bump() {
+    let mut program_test = program_test();
+    let admin = Keypair::new();
+    program_test.add_account(
+        find_config_pda().0,
+        config_account(&admin.pubkey(), &Pubkey::new_unique(), 30, false),
+    );
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+
+    // A second allow-list under another bump would be ignored by Execute
+    let (allow_list_pda, allow_list_bump) = find_non_canonical_pda(&[b"allow_list"]);
+    let mut instruction = update_allow_list_instruction(&admin.pubkey(), &[TOKEN_PROGRAM_ID]);
+    instruction.accounts[2].pubkey = allow_list_pda;
+    instruction.data[1] = allow_list_bump;
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &admin], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidAllowListAccount as u32)
+        )
+    );
+}
@@ -1,12 +1,20 @@
+mod common;
+
+use bytemuck::Zeroable;
+use common::*;
 use light_hasher::{Hasher, Keccak};
-use sol_ver::state::order::Order;
+use sol_ver::error::SolverError;
+use sol_ver::instruction::route::{parse_route, Route, RouteHop};
+use sol_ver::state::oracle::OraclePrice;
+use sol_ver::state::order::{FeeKind, Order, OrderMode, OrderType};
 use solana_program::pubkey::Pubkey;
 use solana_program_test::*;
 use solana_sdk::{
     account::Account,
-    instruction::{AccountMeta, Instruction},
+    clock::Clock,
+    instruction::{AccountMeta, Instruction, InstructionError},
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 // Helper to run the test properly with setup
@@ -27,6 +35,9 @@ async fn test_execute_order_e2e() {
     let order_struct = Order {
         from_token_account: pinocchio::pubkey::Pubkey::from(from_token_account.to_bytes()),
         to_token_account: pinocchio::pubkey::Pubkey::from(to_token_account.to_bytes()),
+        // The order swaps a mint for itself
+        sell_mint: pinocchio::pubkey::Pubkey::from(mint_account.to_bytes()),
+        buy_mint: pinocchio::pubkey::Pubkey::from(mint_account.to_bytes()),
         sell_amount: 100,
         buy_amount: 100, // We need to transfer >= 100
         referral_fee: 0,
@@ -34,7 +45,8 @@ async fn test_execute_order_e2e() {
         minimun_buy_amount: 90,
         amount_decrease_per_second: 0,
         start_time: 0,
-        deadline: 1000,
+        deadline: u64::MAX,
+        ..Zeroable::zeroed()
     };
 
     let order_bytes = unsafe {
@@ -43,7 +55,7 @@ async fn test_execute_order_e2e() {
             core::mem::size_of::<Order>(),
         )
     };
-    let intent_hash = Keccak::hashv(&[&order_bytes]).unwrap();
+    let intent_hash = Keccak::hashv(&[order_bytes]).unwrap();
     let owner = Keypair::new();
 
     let (order_pda, order_bump) = Pubkey::find_program_address(
@@ -54,12 +66,12 @@ async fn test_execute_order_e2e() {
     // Add Order Account
     let account = Account {
         lamports: 1_000_000,
-        data: order_bytes.to_vec(),
+        data: order_account_data(&order_struct, 0),
         owner: program_id,
         executable: false,
         rent_epoch: 0,
     };
-    program_test.add_account(order_pda, account.into());
+    program_test.add_account(order_pda, account);
 
     let token_program_id = solana_sdk::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 
@@ -84,8 +96,7 @@ async fn test_execute_order_e2e() {
             owner: token_program_id,
             executable: false,
             rent_epoch: 0,
-        }
-        .into(),
+        },
     );
 
     // Setup To Token Account (Destination) - Initial Balance 0
@@ -93,7 +104,7 @@ async fn test_execute_order_e2e() {
                                             // Mint
     to_token_data[0..32].copy_from_slice(mint_account.as_ref());
     // Owner
-    to_token_data[32..64].copy_from_slice(Pubkey::new_unique().as_ref());
+    to_token_data[32..64].copy_from_slice(owner.pubkey().as_ref());
     // Amount: 0
     to_token_data[64..72].copy_from_slice(&0u64.to_le_bytes());
     // Delegate: None
@@ -111,8 +122,13 @@ async fn test_execute_order_e2e() {
             owner: token_program_id,
             executable: false,
             rent_epoch: 0,
-        }
-        .into(),
+        },
+    );
+
+    // Setup From Token Account - the owner's remaining sell tokens
+    program_test.add_account(
+        from_token_account,
+        token_account(&mint_account, &owner.pubkey(), 900),
     );
 
     // Setup Solver Token Account (Source) - Initial Balance 1000
@@ -136,23 +152,36 @@ async fn test_execute_order_e2e() {
             owner: token_program_id,
             executable: false,
             rent_epoch: 0,
-        }
-        .into(),
+        },
+    );
+
+    // Setup Config - no protocol fee
+    let (config_pda, _) = find_config_pda();
+    program_test.add_account(
+        config_pda,
+        config_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 0, false),
     );
 
+    // Setup Allow-list - the route only calls the token program
+    let (allow_list_pda, _) = find_allow_list_pda();
+    program_test.add_account(allow_list_pda, allow_list_account(&[token_program_id]));
+
+    // Setup Vault - holds the escrowed sell amount
+    let vault = find_vault(&order_pda, &mint_account);
+    program_test.add_account(vault, token_account(&mint_account, &order_pda, 100));
+
     let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
 
-    // Construct CPI Instruction: Transfer 100 tokens from solver_token_account to to_token_account
+    // Construct the route: one hop transferring 100 tokens from solver_token_account to
+    // to_token_account
     // spl_token instruction: Transfer { amount }
-    // 3 is Transfer
-    let amount_to_transfer = 100u64;
-    let mut cpi_instruction_data = vec![3];
-    cpi_instruction_data.extend_from_slice(&amount_to_transfer.to_le_bytes());
+    let route = transfer_route(100);
 
     let mut instruction_data = vec![2]; // Discriminator Execute
     instruction_data.push(order_bump);
     instruction_data.extend_from_slice(order_bytes);
-    instruction_data.extend_from_slice(&cpi_instruction_data);
+    instruction_data.extend_from_slice(&100u64.to_le_bytes()); // Fill the whole order
+    instruction_data.extend_from_slice(&route);
 
     let accounts = vec![
         AccountMeta::new(solver.pubkey(), true), // Solver Signer
@@ -161,10 +190,18 @@ async fn test_execute_order_e2e() {
         AccountMeta::new(from_token_account, false),
         AccountMeta::new(to_token_account, false), // Writable for balance check
         AccountMeta::new(referral_token_account, false),
-        AccountMeta::new_readonly(token_program_id, false), // Order Program = Token Program
         AccountMeta::new_readonly(token_program_id, false), // Token Program
-        // Remaining Accounts for CPI Transfer
-        // source, destination, authority
+        AccountMeta::new_readonly(mint_account, false),     // Sell Mint
+        AccountMeta::new(vault, false),                     // Vault
+        AccountMeta::new(solver_token_account, false),      // Solver Token Account
+        AccountMeta::new_readonly(config_pda, false),       // Config
+        AccountMeta::new_readonly(Pubkey::new_unique(), false), // Treasury (no fee)
+        AccountMeta::new(find_nonce_pda(&owner.pubkey(), order_struct.nonce), false),
+        AccountMeta::new_readonly(Pubkey::default(), false), // System Program
+        AccountMeta::new_readonly(allow_list_pda, false),    // Allow-list
+        // Remaining Accounts for the route
+        // program, then the Transfer accounts: source, destination, authority
+        AccountMeta::new_readonly(token_program_id, false),
         AccountMeta::new(solver_token_account, false),
         AccountMeta::new(to_token_account, false),
         AccountMeta::new(solver.pubkey(), true), // Authority (Solver) - must be signer in CPI if not PDA
@@ -203,29 +240,1208 @@ async fn test_execute_order_e2e() {
     assert_eq!(amount, 100);
 }
 
-pub fn sol_ver_adapter(
-    program_id: &Pubkey,
-    accounts: &[solana_sdk::account_info::AccountInfo],
-    instruction_data: &[u8],
-) -> solana_program::entrypoint::ProgramResult {
-    // Transmute Solana SDK types to Pinocchio types
-    // Pinocchio types are repr(C) compliant with Solana types
-    let p_program_id: &pinocchio::pubkey::Pubkey = unsafe { std::mem::transmute(program_id) };
-    let p_accounts: &[pinocchio::account_info::AccountInfo] =
-        unsafe { std::mem::transmute(accounts) };
-
-    sol_ver::instruction::process_instruction(p_program_id, p_accounts, instruction_data).map_err(
-        |e| match e {
-            pinocchio::program_error::ProgramError::Custom(c) => {
-                solana_sdk::program_error::ProgramError::Custom(c)
-            }
-            pinocchio::program_error::ProgramError::InvalidArgument => {
-                solana_sdk::program_error::ProgramError::InvalidArgument
-            }
-            pinocchio::program_error::ProgramError::InvalidInstructionData => {
-                solana_sdk::program_error::ProgramError::InvalidInstructionData
-            }
-            _ => solana_sdk::program_error::ProgramError::Custom(999),
+#[tokio::test]
+async fn test_execute_order_delegate_mode() {
+    let fixture = setup_execute(|order| order.mode = OrderMode::Delegate as u8);
+    let instruction = execute_instruction(&fixture, 100);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // The solver is paid the sell side out of the owner's account
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.solver_sell_token_account).await,
+        100
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.to_token_account).await,
+        100
+    );
+
+    // The delegation is fully consumed by the fill
+    let from_account = banks_client
+        .get_account(fixture.from_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(token_amount(&from_account.data), 900);
+    assert_eq!(token_delegate(&from_account.data), None);
+}
+
+#[tokio::test]
+async fn test_execute_order_escrow_mode() {
+    let fixture = setup_execute(|_| {});
+    let instruction = execute_instruction(&fixture, 100);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // The solver is paid the sell side out of the vault
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.solver_sell_token_account).await,
+        100
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.to_token_account).await,
+        100
+    );
+
+    // The fully filled order closes along with its empty vault
+    assert!(banks_client
+        .get_account(fixture.vault)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(banks_client
+        .get_account(fixture.order_pda)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_solver_account_with_wrong_mint() {
+    let fixture = setup_execute(|_| {});
+    let mut instruction = execute_instruction(&fixture, 100);
+    // Solver Token Account holding the buy mint instead of the sell mint
+    instruction.accounts[9].pubkey = fixture.solver_buy_token_account;
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidTokenAccountMint as u32)
+        )
+    );
+}
+
+/// Runs a full fill with the owner's `to_token_account` replaced by the built account.
+async fn execute_with_to_token_account(
+    build: impl FnOnce(&ExecuteFixture) -> Account,
+) -> TransactionError {
+    let mut fixture = setup_execute(|_| {});
+    let account = build(&fixture);
+    fixture
+        .program_test
+        .add_account(fixture.to_token_account, account);
+    let instruction = execute_instruction(&fixture, 100);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_to_account_with_wrong_mint() {
+    let err = execute_with_to_token_account(|fixture| {
+        token_account(&fixture.sell_mint, &fixture.owner.pubkey(), 0)
+    })
+    .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidTokenAccountMint as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_to_account_of_other_owner() {
+    let err = execute_with_to_token_account(|fixture| {
+        token_account(&fixture.buy_mint, &Pubkey::new_unique(), 0)
+    })
+    .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidTokenAccountOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_to_account_outside_token_program() {
+    // Token account data spoofed by another program
+    let err = execute_with_to_token_account(|fixture| Account {
+        owner: Pubkey::new_unique(),
+        ..token_account(&fixture.buy_mint, &fixture.owner.pubkey(), 0)
+    })
+    .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidTokenAccountOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_malformed_to_account() {
+    let err = execute_with_to_token_account(|_| Account {
+        lamports: 1_000_000_000,
+        data: vec![0; 10],
+        owner: TOKEN_PROGRAM_ID,
+        executable: false,
+        rent_epoch: 0,
+    })
+    .await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidTokenAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_bump_deriving_no_address() {
+    let fixture = setup_execute(|_| {});
+    let mut instruction = execute_instruction(&fixture, 100);
+    let intent_hash = Keccak::hashv(&[&order_bytes(&fixture.order)]).unwrap();
+    instruction.data[1] = find_invalid_bump(&[
+        b"order",
+        fixture.owner.pubkey().as_ref(),
+        intent_hash.as_ref(),
+    ]);
+
+    assert_eq!(
+        execute_err(fixture, instruction).await,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidPda as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_truncated_arguments() {
+    let fixture = setup_execute(|_| {});
+    let mut instruction = execute_instruction(&fixture, 100);
+    // The order is cut short and no fill amount follows
+    instruction.data.truncate(1 + 10);
+
+    assert_eq!(
+        execute_err(fixture, instruction).await,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidInstructionData as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_read_only_order_account() {
+    let fixture = setup_execute(|_| {});
+    let mut instruction = execute_instruction(&fixture, 100);
+    instruction.accounts[1].is_writable = false;
+
+    assert_eq!(
+        execute_err(fixture, instruction).await,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::OrderAccountMustBeMut as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_wrong_system_program() {
+    let fixture = setup_execute(|_| {});
+    let mut instruction = execute_instruction(&fixture, 100);
+    instruction.accounts[13] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+
+    assert_eq!(
+        execute_err(fixture, instruction).await,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidProgramId as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_aliased_accounts() {
+    let fixture = setup_execute(|_| {});
+    let mut instruction = execute_instruction(&fixture, 100);
+    // The solver's sell token account doubles as the owner's destination
+    instruction.accounts[4] = AccountMeta::new(fixture.solver_sell_token_account, false);
+
+    assert_eq!(
+        execute_err(fixture, instruction).await,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::DuplicateAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_treasury_aliasing_solver_account() {
+    let fixture = setup_execute_with_config(|_| {}, 100, false);
+    let mut instruction = execute_instruction(&fixture, 100);
+    // The solver would collect the protocol fee on top of its share
+    instruction.accounts[11] = AccountMeta::new(fixture.solver_sell_token_account, false);
+
+    assert_eq!(
+        execute_err(fixture, instruction).await,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::DuplicateAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_read_only_referral_account() {
+    let fixture = setup_execute(|order| {
+        order.referral_fee = 500; // 5%
+        order.referral_fee_kind = FeeKind::Bps as u8;
+    });
+    let mut instruction = execute_instruction(&fixture, 100);
+    instruction.accounts[5].is_writable = false;
+
+    assert_eq!(
+        execute_err(fixture, instruction).await,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::AccountMustBeMut as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_escrow_order_rejects_read_only_vault() {
+    let fixture = setup_execute(|order| order.mode = OrderMode::Escrow as u8);
+    let mut instruction = execute_instruction(&fixture, 100);
+    instruction.accounts[8].is_writable = false;
+
+    assert_eq!(
+        execute_err(fixture, instruction).await,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::AccountMustBeMut as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_pays_referral_fee() {
+    let fixture = setup_execute(|order| {
+        order.referral_fee = 500; // 5%
+        order.referral_fee_kind = FeeKind::Bps as u8;
+    });
+    let instruction = execute_instruction(&fixture, 100);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // The referrer gets 5% of the sell side, the solver the rest
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.referral_token_account).await,
+        5
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.solver_sell_token_account).await,
+        95
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_pays_protocol_fee() {
+    let fixture = setup_execute_with_config(
+        |order| {
+            order.referral_fee = 500; // 5%
+            order.referral_fee_kind = FeeKind::Bps as u8;
+        },
+        1_000, // 10%
+        false,
+    );
+    let instruction = execute_instruction(&fixture, 100);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // The treasury and the referrer are paid alongside each other
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.treasury_token_account).await,
+        10
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.referral_token_account).await,
+        5
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.solver_sell_token_account).await,
+        85
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_while_paused() {
+    let fixture = setup_execute_with_config(|_| {}, 0, true);
+    let instruction = execute_instruction(&fixture, 100);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::ProgramPaused as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_program_outside_allow_list() {
+    let fixture = setup_execute(|_| {});
+    let mut instruction = execute_instruction(&fixture, 100);
+    // A program that would receive the order PDA's signer authority
+    let malicious_program = Pubkey::new_unique();
+    instruction.accounts[REMAINING_ACCOUNTS] = AccountMeta::new_readonly(malicious_program, false);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::ProgramNotAllowed as u32)
+        )
+    );
+
+    // Nothing moved
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.solver_sell_token_account).await,
+        0
+    );
+}
+
+/// Runs a route moving 100 buy tokens from an account owned by the order PDA, which only
+/// works when the route is signed by the order.
+async fn execute_route_spending_order_tokens(sign_route: bool) -> Result<(), TransactionError> {
+    let mut fixture = setup_execute(|order| order.sign_route = sign_route as u8);
+    let order_buy_token_account = Pubkey::new_unique();
+    fixture.program_test.add_account(
+        order_buy_token_account,
+        token_account(&fixture.buy_mint, &fixture.order_pda, 100),
+    );
+
+    let mut instruction = execute_instruction(&fixture, 100);
+    let route_accounts = instruction.accounts.len() - 3;
+    instruction.accounts.truncate(route_accounts);
+    instruction.accounts.extend([
+        AccountMeta::new(order_buy_token_account, false),
+        AccountMeta::new(fixture.to_token_account, false),
+        AccountMeta::new(fixture.order_pda, false),
+    ]);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client
+        .process_transaction(tx)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_execute_order_route_runs_without_order_signer() {
+    let err = execute_route_spending_order_tokens(false)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_route_signed_when_opted_in() {
+    execute_route_spending_order_tokens(true).await.unwrap();
+}
+
+/// Swaps the route of an Execute instruction built by `execute_instruction`.
+fn replace_route(instruction: &mut Instruction, route_amount: u64, route: &[u8]) {
+    let route_len = transfer_route(route_amount).len();
+    instruction
+        .data
+        .truncate(instruction.data.len() - route_len);
+    instruction.data.extend_from_slice(route);
+}
+
+#[tokio::test]
+async fn test_execute_order_multi_hop_route() {
+    let fixture = setup_execute(|_| {});
+    let mut instruction = execute_instruction(&fixture, 100);
+    // Two hops, each delivering half of the buy amount, checked once at the end
+    let hop = token_transfer_data(50);
+    replace_route(
+        &mut instruction,
+        100,
+        &route_data(&[(0, 1, 3, &hop), (0, 1, 3, &hop)]),
+    );
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.to_token_account).await,
+        100
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.solver_buy_token_account).await,
+        900
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_unlisted_program_in_later_hop() {
+    let fixture = setup_execute(|_| {});
+    let mut instruction = execute_instruction(&fixture, 100);
+    instruction
+        .accounts
+        .push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+    let hop = token_transfer_data(50);
+    replace_route(
+        &mut instruction,
+        100,
+        &route_data(&[(0, 1, 3, &hop), (4, 1, 3, &hop)]),
+    );
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::ProgramNotAllowed as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_route_overspending_sell_side() {
+    let fixture = setup_execute(|order| {
+        order.allow_partial_fill = 1;
+        order.sign_route = 1;
+    });
+    let mut instruction = partial_execute_instruction(&fixture, 50, 50);
+    // Besides delivering the buy side, the signed route pulls more out of the vault
+    instruction.accounts.extend([
+        AccountMeta::new(fixture.vault, false),
+        AccountMeta::new(fixture.solver_sell_token_account, false),
+        AccountMeta::new(fixture.order_pda, false),
+    ]);
+    replace_route(
+        &mut instruction,
+        50,
+        &route_data(&[
+            (0, 1, 3, &token_transfer_data(50)),
+            (0, 4, 3, &token_transfer_data(30)),
+        ]),
+    );
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::OverSpent as u32)
+        )
+    );
+    assert_eq!(token_balance(&mut banks_client, fixture.vault).await, 100);
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_signed_route_taking_over_vault() {
+    let fixture = setup_execute(|order| {
+        order.allow_partial_fill = 1;
+        order.sign_route = 1;
+    });
+    let mut instruction = partial_execute_instruction(&fixture, 50, 50);
+    // Besides delivering the buy side, the signed route makes the solver owner of the vault
+    instruction.accounts.extend([
+        AccountMeta::new(fixture.vault, false),
+        AccountMeta::new_readonly(fixture.order_pda, false),
+    ]);
+    let mut set_authority = vec![6, 2, 1]; // spl_token SetAuthority, AccountOwner, Some
+    set_authority.extend_from_slice(fixture.solver.pubkey().as_ref());
+    replace_route(
+        &mut instruction,
+        50,
+        &route_data(&[
+            (0, 1, 3, &token_transfer_data(50)),
+            (0, 4, 2, &set_authority),
+        ]),
+    );
+
+    let err = execute_err(fixture, instruction).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::SellAuthorityChanged as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_second_execution() {
+    let fixture = setup_execute(|_| {});
+    let first = execute_instruction(&fixture, 100);
+    // Same intent, different route data so the transaction is not deduplicated
+    let second = execute_instruction(&fixture, 101);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[first], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // The fully filled order account is closed, so it can't be filled again
+    let mut tx = Transaction::new_with_payer(&[second], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidOrderAccountOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_fills_with_prefunded_nonce_account() {
+    let mut fixture = setup_execute(|_| {});
+    // Lamports sent to the predictable nonce address must not block the nonce word
+    let nonce_pda = find_nonce_pda(&fixture.owner.pubkey(), fixture.order.nonce);
+    fixture.program_test.add_account(
+        nonce_pda,
+        Account {
+            lamports: 1,
+            ..Default::default()
         },
+    );
+    let instruction = execute_instruction(&fixture, 100);
+    let to_token_account = fixture.to_token_account;
+
+    let mut banks_client = execute(fixture, instruction).await.unwrap();
+    assert_eq!(
+        token_balance(&mut banks_client, to_token_account).await,
+        100
+    );
+    let nonce_account = banks_client.get_account(nonce_pda).await.unwrap().unwrap();
+    assert_eq!(nonce_account.owner, Pubkey::new_from_array(sol_ver::ID));
+}
+
+#[tokio::test]
+async fn test_execute_order_partial_fills() {
+    let fixture = setup_execute(|order| order.allow_partial_fill = 1);
+    let first = partial_execute_instruction(&fixture, 40, 40);
+    let second = partial_execute_instruction(&fixture, 60, 60);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[first], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // The fill is tracked and the rest stays escrowed
+    let order_account = banks_client
+        .get_account(fixture.order_pda)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(order_account.data, order_account_data(&fixture.order, 40));
+    assert_eq!(token_balance(&mut banks_client, fixture.vault).await, 60);
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.solver_sell_token_account).await,
+        40
+    );
+
+    let mut tx = Transaction::new_with_payer(&[second], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // Filling the remaining size closes the order
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.to_token_account).await,
+        100
+    );
+    assert!(banks_client
+        .get_account(fixture.order_pda)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_execute_order_partial_fills_split_absolute_referral_fee() {
+    let fixture = setup_execute(|order| {
+        order.allow_partial_fill = 1;
+        order.referral_fee = 10;
+        order.referral_fee_kind = FeeKind::Absolute as u8;
+    });
+    let first = partial_execute_instruction(&fixture, 40, 40);
+    let second = partial_execute_instruction(&fixture, 60, 60);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[first], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // The first fill pays its 40% share of the fee
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.referral_token_account).await,
+        4
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.solver_sell_token_account).await,
+        36
+    );
+
+    let mut tx = Transaction::new_with_payer(&[second], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // Across both fills the referrer gets the fee exactly once
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.referral_token_account).await,
+        10
+    );
+    assert_eq!(
+        token_balance(&mut banks_client, fixture.solver_sell_token_account).await,
+        90
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_partial_fill_when_not_allowed() {
+    let fixture = setup_execute(|_| {});
+    let instruction = partial_execute_instruction(&fixture, 40, 40);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidFillAmount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_limit_order_requires_exact_buy_amount() {
+    let fixture = setup_execute(|order| {
+        order.order_type = OrderType::Limit as u8;
+        order.minimun_buy_amount = 0;
+    });
+    let instruction = execute_instruction(&fixture, 99);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::SlippageExceeded as u32)
+        )
+    );
+}
+
+/// Warps to a later slot and pins the clock to `unix_timestamp`.
+async fn warp_to_timestamp(context: &mut ProgramTestContext, unix_timestamp: i64) {
+    let slot = context.banks_client.get_root_slot().await.unwrap();
+    context.warp_to_slot(slot + 100).unwrap();
+
+    let mut clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    clock.unix_timestamp = unix_timestamp;
+    context.set_sysvar(&clock);
+}
+
+async fn process_with_context(
+    context: &mut ProgramTestContext,
+    solver: &Keypair,
+    instruction: Instruction,
+) -> Result<(), TransactionError> {
+    let recent_blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&context.payer.pubkey()));
+    tx.sign(&[&context.payer, solver], recent_blockhash);
+    context
+        .banks_client
+        .process_transaction(tx)
+        .await
+        .map_err(|err| err.unwrap())
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_expired_order() {
+    let fixture = setup_execute(|order| {
+        order.start_time = 1_000;
+        order.deadline = 2_000;
+    });
+    let instruction = execute_instruction(&fixture, 100);
+    let mut context = fixture.program_test.start_with_context().await;
+
+    warp_to_timestamp(&mut context, 2_001).await;
+    let err = process_with_context(&mut context, &fixture.solver, instruction)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::OrderExpired as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_order_before_start() {
+    let fixture = setup_execute(|order| {
+        order.start_time = 1_000;
+        order.deadline = 2_000;
+    });
+    let instruction = execute_instruction(&fixture, 100);
+    let mut context = fixture.program_test.start_with_context().await;
+
+    warp_to_timestamp(&mut context, 999).await;
+    let err = process_with_context(&mut context, &fixture.solver, instruction.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::OrderNotStarted as u32)
+        )
+    );
+
+    // The same fill goes through once the auction is live
+    warp_to_timestamp(&mut context, 1_500).await;
+    process_with_context(&mut context, &fixture.solver, instruction)
+        .await
+        .unwrap();
+}
+
+/// Sets up a full fill guarded by `oracle` within 5% and 60 seconds.
+fn setup_oracle_execute(setup: ExecuteSetup, oracle: &Pubkey) -> ExecuteFixture {
+    setup_execute_with(setup, |order| {
+        order.oracle = to_pinocchio(oracle);
+        order.oracle_feed_id = ORACLE_FEED_ID;
+        order.oracle_max_deviation_bps = 500;
+        order.oracle_max_staleness = 60;
+    })
+}
+
+/// An Execute instruction for a fixture from [`setup_oracle_execute`], delivering 100 buy
+/// tokens and passing the oracle and the buy mint whose decimals it is scaled by.
+fn oracle_execute_instruction(fixture: &ExecuteFixture, oracle: &Pubkey) -> Instruction {
+    let mut instruction = execute_instruction(fixture, 100);
+    instruction.accounts.extend([
+        AccountMeta::new_readonly(*oracle, false),
+        AccountMeta::new_readonly(fixture.buy_mint, false),
+    ]);
+    instruction
+}
+
+/// Runs a full fill at `unix_timestamp 1_000` between mints of `setup`, guarded by an
+/// oracle quoting `price` (no decimals) last published at `publish_time`.
+async fn execute_with_oracle(
+    setup: ExecuteSetup,
+    price: u64,
+    publish_time: i64,
+) -> Result<(), TransactionError> {
+    let oracle = Pubkey::new_unique();
+    let mut fixture = setup_oracle_execute(setup, &oracle);
+    fixture
+        .program_test
+        .add_account(oracle, oracle_account(price, 0, publish_time));
+
+    let instruction = oracle_execute_instruction(&fixture, &oracle);
+    let mut context = fixture.program_test.start_with_context().await;
+
+    warp_to_timestamp(&mut context, 1_000).await;
+    process_with_context(&mut context, &fixture.solver, instruction).await
+}
+
+#[tokio::test]
+async fn test_execute_order_within_oracle_deviation() {
+    execute_with_oracle(ExecuteSetup::default(), 1, 990)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_oracle_deviation() {
+    // The oracle quotes 200 buy tokens for the fill, the route only delivers 100
+    let err = execute_with_oracle(ExecuteSetup::default(), 2, 990)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::OraclePriceDeviation as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_stale_oracle() {
+    let err = execute_with_oracle(ExecuteSetup::default(), 1, 900)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::OraclePriceStale as u32)
+        )
+    );
+}
+
+/// Mints of different precision: 100 base units of a 9 decimals sell mint for 100 base
+/// units of a 6 decimals buy mint trade at 1000 whole buy tokens per sell token.
+fn mixed_decimals_setup() -> ExecuteSetup {
+    ExecuteSetup {
+        sell_decimals: 9,
+        buy_decimals: 6,
+        ..ExecuteSetup::default()
+    }
+}
+
+#[tokio::test]
+async fn test_execute_order_scales_oracle_price_by_mint_decimals() {
+    execute_with_oracle(mixed_decimals_setup(), 1_000, 990)
+        .await
+        .unwrap();
+
+    // The oracle quotes 200 buy base units for the fill, the route only delivers 100
+    let err = execute_with_oracle(mixed_decimals_setup(), 2_000, 990)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::OraclePriceDeviation as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_oracle_of_other_feed() {
+    let oracle = Pubkey::new_unique();
+    let mut fixture = setup_execute(|order| {
+        order.oracle = to_pinocchio(&oracle);
+        order.oracle_feed_id = [8; 32];
+        order.oracle_max_deviation_bps = 500;
+        order.oracle_max_staleness = 60;
+    });
+    // A genuine price update, but of a feed the owner didn't pick
+    fixture
+        .program_test
+        .add_account(oracle, oracle_account(1, 0, 990));
+    let instruction = oracle_execute_instruction(&fixture, &oracle);
+
+    let err = execute_err(fixture, instruction).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::OracleFeedMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_oracle_of_other_program() {
+    let oracle = Pubkey::new_unique();
+    let mut fixture = setup_oracle_execute(ExecuteSetup::default(), &oracle);
+    // A well-formed price update anyone could have written
+    let mut spoofed = oracle_account(1, 0, 990);
+    spoofed.owner = Pubkey::new_unique();
+    fixture.program_test.add_account(oracle, spoofed);
+
+    let instruction = oracle_execute_instruction(&fixture, &oracle);
+
+    let err = execute_err(fixture, instruction).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidOracleAccount as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_missing_oracle() {
+    let oracle = Pubkey::new_unique();
+    let fixture = setup_oracle_execute(ExecuteSetup::default(), &oracle);
+    let instruction = execute_instruction(&fixture, 100);
+
+    let err = execute_err(fixture, instruction).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidOracleAccount as u32)
+        )
+    );
+}
+
+#[test]
+fn test_oracle_price_checks() {
+    // 1.5 buy tokens per sell token
+    let price = OraclePrice {
+        price: 15,
+        publish_time: 1_000,
+        decimals: 1,
+    };
+
+    assert!(price.check_fresh(1_060, 60).is_ok());
+    assert!(price.check_fresh(1_061, 60).is_err());
+    // A price from the future is not trusted either
+    assert!(price.check_fresh(999, 60).is_err());
+
+    // 1% below the oracle's 150 is fine, 2% is not
+    assert!(price.check_deviation(100, 150, 0, 6, 6).is_ok());
+    assert!(price.check_deviation(100, 149, 0, 6, 6).is_err());
+    assert!(price.check_deviation(100, 148, 100, 6, 6).is_ok());
+    assert!(price.check_deviation(100, 146, 100, 6, 6).is_err());
+
+    // Amounts are in base units, so the buy side is scaled by the decimals difference
+    assert!(price.check_deviation(100, 150_000, 0, 6, 9).is_ok());
+    assert!(price.check_deviation(100, 149_999, 0, 6, 9).is_err());
+    assert!(price.check_deviation(1_000, 1, 0, 9, 6).is_ok());
+    assert!(price.check_deviation(1_000, 0, 0, 9, 6).is_err());
+
+    // More than 100% is rejected
+    assert!(price.check_deviation(100, 150, 10_001, 6, 6).is_err());
+}
+
+#[test]
+fn test_parse_price_update() {
+    let price = OraclePrice::parse_price_update(&price_update_data(15, -1, 1_000), &ORACLE_FEED_ID)
+        .unwrap();
+    assert_eq!(
+        price,
+        OraclePrice {
+            price: 15,
+            publish_time: 1_000,
+            decimals: 1,
+        }
+    );
+
+    // Only fully verified updates are trusted
+    let mut partial = price_update_data(15, -1, 1_000);
+    partial[40] = 0;
+    assert!(OraclePrice::parse_price_update(&partial, &ORACLE_FEED_ID).is_err());
+
+    // Another account type of the receiver program
+    let mut other = price_update_data(15, -1, 1_000);
+    other[0] ^= 1;
+    assert!(OraclePrice::parse_price_update(&other, &ORACLE_FEED_ID).is_err());
+
+    // Prices that can't be a buy-per-sell ratio
+    assert!(
+        OraclePrice::parse_price_update(&price_update_data(0, -1, 1_000), &ORACLE_FEED_ID).is_err()
+    );
+    assert!(
+        OraclePrice::parse_price_update(&price_update_data(-15, -1, 1_000), &ORACLE_FEED_ID)
+            .is_err()
+    );
+    assert!(
+        OraclePrice::parse_price_update(&price_update_data(15, 1, 1_000), &ORACLE_FEED_ID).is_err()
+    );
+    assert!(
+        OraclePrice::parse_price_update(&price_update_data(15, -20, 1_000), &ORACLE_FEED_ID)
+            .is_err()
+    );
+
+    // Another feed
+    assert!(OraclePrice::parse_price_update(&price_update_data(15, -1, 1_000), &[8; 32]).is_err());
+
+    // Truncated account
+    assert!(OraclePrice::parse_price_update(
+        &price_update_data(15, -1, 1_000)[..80],
+        &ORACLE_FEED_ID
     )
+    .is_err());
+}
+
+#[test]
+fn test_parse_route() {
+    let first = token_transfer_data(60);
+    let second = [7u8; 3];
+    let data = route_data(&[(0, 1, 3, &first), (4, 5, 2, &second)]);
+
+    let route = parse_route(&data).unwrap();
+    assert_eq!(
+        route,
+        Route {
+            hook_accounts: 0,
+            hops: vec![
+                RouteHop {
+                    program_index: 0,
+                    accounts: 1..4,
+                    data: &first,
+                },
+                RouteHop {
+                    program_index: 4,
+                    accounts: 5..7,
+                    data: &second,
+                },
+            ],
+        }
+    );
+
+    // The sell mint's transfer hook accounts come first in the remaining accounts
+    let mut with_hooks = data.clone();
+    with_hooks[0] = 2;
+    assert_eq!(parse_route(&with_hooks).unwrap().hook_accounts, 2);
+
+    // Empty, truncated and over-long routes are rejected
+    assert!(parse_route(&[0]).is_err());
+    assert!(parse_route(&route_data(&[])).is_err());
+    assert!(parse_route(&data[..data.len() - 1]).is_err());
+    assert!(parse_route(&[data.as_slice(), &[0]].concat()).is_err());
+}
+
+#[test]
+fn test_referral_fee_amount() {
+    let mut order = Order {
+        referral_fee: 250,
+        referral_fee_kind: FeeKind::Bps as u8,
+        ..Zeroable::zeroed()
+    };
+    assert_eq!(order.referral_fee_amount(0, 10_000).unwrap(), 250);
+    assert_eq!(
+        order.referral_fee_amount(0, u64::MAX).unwrap(),
+        u64::MAX / 40
+    );
+
+    // More than 100% is rejected
+    order.referral_fee = 10_001;
+    assert!(order.referral_fee_amount(0, 10_000).is_err());
+
+    order.sell_amount = 10_000;
+    order.referral_fee = 30;
+    order.referral_fee_kind = FeeKind::Absolute as u8;
+    assert_eq!(order.referral_fee_amount(0, 10_000).unwrap(), 30);
+
+    // Partial fills each pay their share, the last one the rounding remainder
+    assert_eq!(order.referral_fee_amount(0, 3_333).unwrap(), 9);
+    assert_eq!(order.referral_fee_amount(3_333, 3_333).unwrap(), 10);
+    assert_eq!(order.referral_fee_amount(6_666, 3_334).unwrap(), 11);
+
+    // A fill too small to owe a whole token pays nothing instead of failing
+    assert_eq!(order.referral_fee_amount(0, 10).unwrap(), 0);
+
+    // An absolute fee larger than the order is rejected
+    order.referral_fee = 10_001;
+    assert!(order.referral_fee_amount(0, 10_000).is_err());
+}
+
+#[test]
+fn test_partial_fill_amounts() {
+    let mut order = Order {
+        sell_amount: 3,
+        buy_amount: 100,
+        minimun_buy_amount: 100,
+        deadline: u64::MAX,
+        ..Zeroable::zeroed()
+    };
+
+    // Without partial fills only the full size is accepted
+    assert!(order.check_fill_amount(0, 3).is_ok());
+    assert!(order.check_fill_amount(0, 1).is_err());
+
+    order.allow_partial_fill = 1;
+    assert!(order.check_fill_amount(0, 1).is_ok());
+    assert!(order.check_fill_amount(2, 1).is_ok());
+    assert!(order.check_fill_amount(2, 2).is_err());
+    assert!(order.check_fill_amount(0, 0).is_err());
+
+    // The buy amount is pro-rata, rounded up for the owner
+    assert_eq!(order.required_buy_amount(0, 1).unwrap(), 34);
+    assert_eq!(order.required_buy_amount(0, 3).unwrap(), 100);
 }
@@ -0,0 +1,362 @@
+mod common;
+
+use bytemuck::Zeroable;
+use common::*;
+use sol_ver::error::SolverError;
+use sol_ver::state::nonce::NonceBitmap;
+use sol_ver::state::order::{Order, OrderMode};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    signature::{Keypair, Signer},
+    sysvar,
+    transaction::{Transaction, TransactionError},
+};
+
+/// A delegate-mode order that was never initialized on-chain. The owner approved their
+/// authority PDA once, for more than the order sells.
+struct SignedOrderFixture {
+    program_test: ProgramTest,
+    owner: Keypair,
+    solver: Keypair,
+    order: Order,
+    authority: Pubkey,
+    authority_bump: u8,
+    sell_mint: Pubkey,
+    from_token_account: Pubkey,
+    to_token_account: Pubkey,
+    solver_sell_token_account: Pubkey,
+    solver_buy_token_account: Pubkey,
+    config: Pubkey,
+}
+
+fn setup_signed_order() -> SignedOrderFixture {
+    let mut program_test = program_test();
+
+    let owner = Keypair::new();
+    let solver = Keypair::new();
+    let sell_mint = Pubkey::new_unique();
+    let buy_mint = Pubkey::new_unique();
+    let from_token_account = Pubkey::new_unique();
+    let to_token_account = Pubkey::new_unique();
+    let solver_sell_token_account = Pubkey::new_unique();
+    let solver_buy_token_account = Pubkey::new_unique();
+    let (authority, authority_bump) = find_authority_pda(&owner.pubkey());
+    let (config, _) = find_config_pda();
+
+    let order = Order {
+        from_token_account: to_pinocchio(&from_token_account),
+        to_token_account: to_pinocchio(&to_token_account),
+        sell_mint: to_pinocchio(&sell_mint),
+        buy_mint: to_pinocchio(&buy_mint),
+        sell_amount: 100,
+        buy_amount: 100,
+        minimun_buy_amount: 90,
+        deadline: u64::MAX,
+        mode: OrderMode::Delegate as u8,
+        ..Zeroable::zeroed()
+    };
+
+    program_test.add_account(sell_mint, mint_account(&Pubkey::new_unique(), 10_000, 6));
+    program_test.add_account(buy_mint, mint_account(&Pubkey::new_unique(), 10_000, 6));
+    program_test.add_account(
+        from_token_account,
+        token_account_with_delegate(&sell_mint, &owner.pubkey(), 1000, Some((&authority, 500))),
+    );
+    program_test.add_account(
+        to_token_account,
+        token_account(&buy_mint, &owner.pubkey(), 0),
+    );
+    program_test.add_account(
+        solver_sell_token_account,
+        token_account(&sell_mint, &solver.pubkey(), 0),
+    );
+    program_test.add_account(
+        solver_buy_token_account,
+        token_account(&buy_mint, &solver.pubkey(), 1000),
+    );
+    program_test.add_account(
+        config,
+        config_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 0, false),
+    );
+    program_test.add_account(
+        find_allow_list_pda().0,
+        allow_list_account(&[TOKEN_PROGRAM_ID]),
+    );
+    program_test.add_account(
+        solver.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            ..Account::default()
+        },
+    );
+
+    SignedOrderFixture {
+        program_test,
+        owner,
+        solver,
+        order,
+        authority,
+        authority_bump,
+        sell_mint,
+        from_token_account,
+        to_token_account,
+        solver_sell_token_account,
+        solver_buy_token_account,
+        config,
+    }
+}
+
+/// Builds an ExecuteSigned instruction whose route transfers `fill_amount` buy tokens
+/// from the solver to the owner.
+fn execute_signed_instruction(fixture: &SignedOrderFixture, fill_amount: u64) -> Instruction {
+    let mut data = vec![6]; // Instruction::ExecuteSigned discriminator
+    data.push(fixture.authority_bump);
+    data.extend_from_slice(&order_bytes(&fixture.order));
+    data.extend_from_slice(&transfer_route(fill_amount));
+
+    Instruction {
+        program_id: Pubkey::new_from_array(sol_ver::ID),
+        accounts: vec![
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            AccountMeta::new(fixture.solver.pubkey(), true),
+            AccountMeta::new_readonly(fixture.authority, false),
+            AccountMeta::new_readonly(fixture.owner.pubkey(), false),
+            AccountMeta::new(fixture.from_token_account, false),
+            AccountMeta::new(fixture.to_token_account, false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false), // Referral (no fee)
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(fixture.sell_mint, false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false), // Vault (unused)
+            AccountMeta::new(fixture.solver_sell_token_account, false),
+            AccountMeta::new_readonly(fixture.config, false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false), // Treasury (no fee)
+            AccountMeta::new(
+                find_nonce_pda(&fixture.owner.pubkey(), fixture.order.nonce),
+                false,
+            ),
+            AccountMeta::new_readonly(Pubkey::default(), false), // System Program
+            AccountMeta::new_readonly(find_allow_list_pda().0, false),
+            // Remaining Accounts for the route: program, source, destination, authority
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new(fixture.solver_buy_token_account, false),
+            AccountMeta::new(fixture.to_token_account, false),
+            AccountMeta::new_readonly(fixture.solver.pubkey(), true),
+        ],
+        data,
+    }
+}
+
+fn assert_invalid_intent_signature(err: TransactionError, instruction_index: u8) {
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            instruction_index,
+            InstructionError::Custom(SolverError::InvalidIntentSignature as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_signed_order() {
+    let fixture = setup_signed_order();
+    let instructions = [
+        ed25519_instruction(&fixture.owner, &order_bytes(&fixture.order)),
+        execute_signed_instruction(&fixture, 100),
+    ];
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let to_account = banks_client
+        .get_account(fixture.to_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(token_amount(&to_account.data), 100);
+
+    let solver_sell_account = banks_client
+        .get_account(fixture.solver_sell_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(token_amount(&solver_sell_account.data), 100);
+
+    // The standing approval is only drawn down by the filled amount
+    let from_account = banks_client
+        .get_account(fixture.from_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(token_amount(&from_account.data), 900);
+    assert_eq!(token_delegate(&from_account.data), Some(fixture.authority));
+}
+
+#[tokio::test]
+async fn test_execute_signed_order_rejects_missing_signature() {
+    let fixture = setup_signed_order();
+    let instruction = execute_signed_instruction(&fixture, 100);
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_invalid_intent_signature(err, 0);
+}
+
+#[tokio::test]
+async fn test_execute_signed_order_rejects_signature_over_other_order() {
+    let fixture = setup_signed_order();
+    // The owner signed an order with a higher buy amount than the one submitted
+    let signed_order = Order {
+        buy_amount: 1_000,
+        ..fixture.order
+    };
+    let instructions = [
+        ed25519_instruction(&fixture.owner, &order_bytes(&signed_order)),
+        execute_signed_instruction(&fixture, 100),
+    ];
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_invalid_intent_signature(err, 1);
+}
+
+#[tokio::test]
+async fn test_execute_signed_order_rejects_other_signer() {
+    let fixture = setup_signed_order();
+    let instructions = [
+        ed25519_instruction(&Keypair::new(), &order_bytes(&fixture.order)),
+        execute_signed_instruction(&fixture, 100),
+    ];
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_invalid_intent_signature(err, 1);
+}
+
+#[tokio::test]
+async fn test_execute_signed_order_rejects_replay() {
+    let fixture = setup_signed_order();
+    let signature = ed25519_instruction(&fixture.owner, &order_bytes(&fixture.order));
+    let first = [signature.clone(), execute_signed_instruction(&fixture, 100)];
+    // The standing approval still covers another fill, only the nonce stops the replay
+    let second = [signature, execute_signed_instruction(&fixture, 101)];
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&first, Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let mut tx = Transaction::new_with_payer(&second, Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SolverError::OrderAlreadyFilled as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_signed_order_rejects_bump_deriving_no_address() {
+    let fixture = setup_signed_order();
+    let mut instruction = execute_signed_instruction(&fixture, 100);
+    instruction.data[1] = find_invalid_bump(&[b"authority", fixture.owner.pubkey().as_ref()]);
+    let instructions = [
+        ed25519_instruction(&fixture.owner, &order_bytes(&fixture.order)),
+        instruction,
+    ];
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SolverError::InvalidPda as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_signed_order_rejects_sign_route() {
+    let mut fixture = setup_signed_order();
+    fixture.order = Order {
+        sign_route: 1,
+        ..fixture.order
+    };
+    let instructions = [
+        ed25519_instruction(&fixture.owner, &order_bytes(&fixture.order)),
+        execute_signed_instruction(&fixture, 100),
+    ];
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+    tx.sign(&[&payer, &fixture.solver], recent_blockhash);
+
+    // Signing the route would hand every hop the owner's standing approvals
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(SolverError::SignRouteNotAllowed as u32)
+        )
+    );
+}
+
+#[test]
+fn test_nonce_bitmap() {
+    let mut bitmap = NonceBitmap::zeroed();
+    assert!(!bitmap.is_used(3));
+
+    bitmap.mark_used(3);
+    assert!(bitmap.is_used(3));
+    assert!(!bitmap.is_used(2));
+    assert!(!bitmap.is_used(4));
+
+    // Nonces wrap within their bitmap account
+    assert!(bitmap.is_used(3 + NonceBitmap::NONCES_PER_ACCOUNT));
+    assert_eq!(NonceBitmap::word(3 + NonceBitmap::NONCES_PER_ACCOUNT), 1);
+}
@@ -1,13 +1,16 @@
-use light_hasher::{Hasher, Keccak};
-use sol_ver::state::order::Order;
+mod common;
+
+use bytemuck::Zeroable;
+use common::*;
+use sol_ver::error::SolverError;
+use sol_ver::state::{curve::AuctionCurve, order::Order};
 use solana_program::pubkey::Pubkey;
 use solana_program_test::*;
 use solana_sdk::{
-    account_info::AccountInfo,
-    entrypoint::ProgramResult,
-    instruction::{AccountMeta, Instruction},
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
     signature::{Keypair, Signer},
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 #[tokio::test]
@@ -20,100 +23,189 @@ async fn test_calculate_current_buy_amount() {
         referral_fee: 0,
         referral_token_account: pinocchio::pubkey::Pubkey::default(),
         minimun_buy_amount: 500, // Floor price
-        _padding: [0; 6],
+        amount_decrease_per_second: 0,
         start_time: 100, // Start Time
         deadline: 200,   // End Time (Duration: 100)
+        ..Zeroable::zeroed()
     };
     // 1. Before start time
-    assert_eq!(order.calculate_current_buy_amount(50), 1000);
+    assert_eq!(order.calculate_current_buy_amount(50).unwrap(), 1000);
     // 2. After deadline
-    assert_eq!(order.calculate_current_buy_amount(250), 500);
+    assert_eq!(order.calculate_current_buy_amount(250).unwrap(), 500);
     // 3. Midway through
-    assert_eq!(order.calculate_current_buy_amount(150), 750);
+    assert_eq!(order.calculate_current_buy_amount(150).unwrap(), 750);
     // 4. Near the end
-    assert_eq!(order.calculate_current_buy_amount(190), 550);
+    assert_eq!(order.calculate_current_buy_amount(190).unwrap(), 550);
 }
 
-#[tokio::test]
-async fn test_initialize_order() {
-    let program_id = Pubkey::new_from_array(sol_ver::ID);
+#[test]
+fn test_calculate_current_buy_amount_per_second() {
+    let order = Order {
+        sell_amount: 1000,
+        buy_amount: 1000,
+        minimun_buy_amount: 500,
+        amount_decrease_per_second: 3,
+        start_time: 100,
+        deadline: 1000,
+        curve: AuctionCurve::PerSecond as u8,
+        ..Zeroable::zeroed()
+    };
+    // 1. Before start time
+    assert_eq!(order.calculate_current_buy_amount(50).unwrap(), 1000);
+    // 2. A fixed decrement per elapsed second
+    assert_eq!(order.calculate_current_buy_amount(110).unwrap(), 970);
+    // 3. Floored at the minimum, well before the deadline
+    assert_eq!(order.calculate_current_buy_amount(400).unwrap(), 500);
+}
 
-    let mut program_test = ProgramTest::new("sol_ver", program_id, None);
-    program_test.prefer_bpf(false);
+#[test]
+fn test_calculate_current_buy_amount_rejects_inconsistent_curve() {
+    let linear = Order {
+        buy_amount: 1000,
+        minimun_buy_amount: 500,
+        start_time: 100,
+        deadline: 200,
+        ..Zeroable::zeroed()
+    };
+    let invalid_curve_parameters = Err(SolverError::InvalidCurveParameters.into());
+
+    // A per-second rate is meaningless for linear interpolation
+    let order = Order {
+        amount_decrease_per_second: 1,
+        ..linear
+    };
+    assert_eq!(
+        order.calculate_current_buy_amount(150),
+        invalid_curve_parameters
+    );
 
-    let token_program_id = solana_sdk::pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
-    program_test.add_program(
-        "spl_token",
-        token_program_id,
-        processor!(mock_token_processor),
+    // Per-second decay needs a rate
+    let order = Order {
+        curve: AuctionCurve::PerSecond as u8,
+        ..linear
+    };
+    assert_eq!(
+        order.calculate_current_buy_amount(150),
+        invalid_curve_parameters
+    );
+
+    // The floor can't exceed the starting amount
+    let order = Order {
+        minimun_buy_amount: 1001,
+        ..linear
+    };
+    assert_eq!(
+        order.calculate_current_buy_amount(150),
+        invalid_curve_parameters
+    );
+
+    let order = Order { curve: 5, ..linear };
+    assert_eq!(
+        order.calculate_current_buy_amount(150),
+        Err(SolverError::InvalidAuctionCurve.into())
     );
+}
+
+struct InitializeFixture {
+    program_test: ProgramTest,
+    owner: Keypair,
+    rent_payer: Keypair,
+    order: Order,
+    order_pda: Pubkey,
+    vault: Pubkey,
+    from_token_account: Pubkey,
+    instruction: Instruction,
+}
+
+fn setup_initialize(is_paused: bool) -> InitializeFixture {
+    let program_id = Pubkey::new_from_array(sol_ver::ID);
 
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let mut program_test = ProgramTest::new("sol_ver", program_id, None);
+    program_test.prefer_bpf(false);
 
     // 1. Setup Accounts
     let owner = Keypair::new();
-    let sell_token_mint = Keypair::new();
-    let buy_token_mint = Keypair::new();
-    let receiver_token_account = Keypair::new();
-    let referral_token_account = Keypair::new();
+    let sell_token_mint = Pubkey::new_unique();
+    let buy_token_mint = Pubkey::new_unique();
+    let from_token_account = Pubkey::new_unique();
+    let to_token_account = Pubkey::new_unique();
+    let referral_token_account = Pubkey::new_unique();
     let rent_payer = Keypair::new();
+    let (config_pda, _) = find_config_pda();
 
-    // Fund owner
-    let transfer_tx = system_transfer(&payer.pubkey(), &owner.pubkey(), 1_000_000_000);
-    let mut tx = Transaction::new_with_payer(&[transfer_tx], Some(&payer.pubkey()));
-    tx.sign(&[&payer], recent_blockhash);
-    banks_client.process_transaction(tx).await.unwrap();
+    program_test.add_account(
+        config_pda,
+        config_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 0, is_paused),
+    );
+    program_test.add_account(
+        sell_token_mint,
+        mint_account(&Pubkey::new_unique(), 1000, 6),
+    );
+    program_test.add_account(
+        from_token_account,
+        token_account(&sell_token_mint, &owner.pubkey(), 1000),
+    );
+    program_test.add_account(
+        to_token_account,
+        token_account(&buy_token_mint, &owner.pubkey(), 0),
+    );
+    program_test.add_account(
+        referral_token_account,
+        token_account(&sell_token_mint, &Pubkey::new_unique(), 0),
+    );
 
-    // 2. Construct Order
-    let p_owner = pinocchio::pubkey::Pubkey::from(owner.pubkey().to_bytes());
-    let p_sell_token = pinocchio::pubkey::Pubkey::from(sell_token_mint.pubkey().to_bytes());
-    let p_buy_token = pinocchio::pubkey::Pubkey::from(buy_token_mint.pubkey().to_bytes());
-    let p_receiver = pinocchio::pubkey::Pubkey::from(receiver_token_account.pubkey().to_bytes());
-    let p_referral = pinocchio::pubkey::Pubkey::from(referral_token_account.pubkey().to_bytes());
-    let p_rent_payer = pinocchio::pubkey::Pubkey::from(rent_payer.pubkey().to_bytes());
+    // Fund owner and rent payer
+    for funded in [owner.pubkey(), rent_payer.pubkey()] {
+        program_test.add_account(
+            funded,
+            Account {
+                lamports: 1_000_000_000,
+                ..Default::default()
+            },
+        );
+    }
 
+    // 2. Construct Order
     let order = Order {
-        from_token_account: p_sell_token,
-        to_token_account: p_buy_token,
+        from_token_account: to_pinocchio(&from_token_account),
+        to_token_account: to_pinocchio(&to_token_account),
+        sell_mint: to_pinocchio(&sell_token_mint),
+        buy_mint: to_pinocchio(&buy_token_mint),
         sell_amount: 100,
         buy_amount: 50,
         referral_fee: 1,
-        referral_token_account: p_referral,
+        referral_token_account: to_pinocchio(&referral_token_account),
         minimun_buy_amount: 45,
-        _padding: [0; 6],
+        amount_decrease_per_second: 0,
         start_time: 1_600_000_000,
         deadline: 1_700_000_000,
+        ..Zeroable::zeroed()
     };
 
-    // Unsafe serialization because Order is repr(C) but not Pod
-    let order_bytes = unsafe {
-        core::slice::from_raw_parts(
-            &order as *const Order as *const u8,
-            core::mem::size_of::<Order>(),
-        )
-    };
-    let mut intent_body = Vec::new();
-    intent_body.extend_from_slice(order_bytes);
-
-    let intent_hash = Keccak::hashv(&[&intent_body]).unwrap();
-
-    let (order_pda, bump) = Pubkey::find_program_address(
-        &[b"order", owner.pubkey().as_ref(), intent_hash.as_ref()],
-        &program_id,
-    );
+    let intent_body = order_bytes(&order);
+    let (order_pda, bump) = find_order_pda(&owner.pubkey(), &intent_body);
+    let vault = find_vault(&order_pda, &sell_token_mint);
 
     // 3. Construct Instruction Data
-    // [discriminator] + [bump] + [nonce] + [Order]
+    // [discriminator] + [bump] + [Order]
     let mut instruction_data = Vec::new();
     instruction_data.push(0); // Instruction::Initialize discriminator
     instruction_data.push(bump);
     instruction_data.extend_from_slice(&intent_body);
 
-    let from_token_account = Keypair::new();
-
     let accounts = vec![
         AccountMeta::new(owner.pubkey(), true),
         AccountMeta::new(order_pda, false),
+        AccountMeta::new(rent_payer.pubkey(), true),
+        AccountMeta::new(from_token_account, false),
+        AccountMeta::new(vault, false),
+        AccountMeta::new_readonly(sell_token_mint, false),
+        AccountMeta::new_readonly(Pubkey::default(), false), // System Program
+        AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+        AccountMeta::new_readonly(config_pda, false),
+        AccountMeta::new_readonly(to_token_account, false),
+        AccountMeta::new_readonly(referral_token_account, false),
     ];
 
     let instruction = Instruction {
@@ -122,27 +214,77 @@ async fn test_initialize_order() {
         data: instruction_data,
     };
 
-    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
-    tx.sign(&[&payer, &owner], recent_blockhash);
+    InitializeFixture {
+        program_test,
+        owner,
+        rent_payer,
+        order,
+        order_pda,
+        vault,
+        from_token_account,
+        instruction,
+    }
+}
+
+#[tokio::test]
+async fn test_initialize_order() {
+    let fixture = setup_initialize(false);
+    let program_id = Pubkey::new_from_array(sol_ver::ID);
+    let (order, order_pda, vault, from_token_account) = (
+        fixture.order,
+        fixture.order_pda,
+        fixture.vault,
+        fixture.from_token_account,
+    );
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[fixture.instruction], Some(&payer.pubkey()));
+    tx.sign(
+        &[&payer, &fixture.owner, &fixture.rent_payer],
+        recent_blockhash,
+    );
 
     banks_client.process_transaction(tx).await.unwrap();
-}
 
-pub fn mock_token_processor(
-    _program_id: &Pubkey,
-    _accounts: &[AccountInfo],
-    _instruction_data: &[u8],
-) -> ProgramResult {
-    Ok(())
+    // 4. The order PDA is created, owned by the program and holds the intent
+    let order_account = banks_client.get_account(order_pda).await.unwrap().unwrap();
+    assert_eq!(order_account.owner, program_id);
+    assert_eq!(order_account.data, order_account_data(&order, 0));
+
+    // 5. The sell tokens are escrowed in the vault owned by the order PDA
+    let vault_account = banks_client.get_account(vault).await.unwrap().unwrap();
+    assert_eq!(token_amount(&vault_account.data), 100);
+    let from_account = banks_client
+        .get_account(from_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(token_amount(&from_account.data), 900);
 }
 
-fn system_transfer(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
-    let account_metas = vec![AccountMeta::new(*from, true), AccountMeta::new(*to, false)];
-    let mut data = vec![2, 0, 0, 0]; // Transfer instruction index = 2
-    data.extend_from_slice(&lamports.to_le_bytes());
-    Instruction {
-        program_id: Pubkey::default(), // System Program ID is [0; 32]
-        accounts: account_metas,
-        data,
-    }
+#[tokio::test]
+async fn test_initialize_order_rejects_when_paused() {
+    let fixture = setup_initialize(true);
+    let order_pda = fixture.order_pda;
+    let (mut banks_client, payer, recent_blockhash) = fixture.program_test.start().await;
+
+    let mut tx = Transaction::new_with_payer(&[fixture.instruction], Some(&payer.pubkey()));
+    tx.sign(
+        &[&payer, &fixture.owner, &fixture.rent_payer],
+        recent_blockhash,
+    );
+
+    let err = banks_client
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::ProgramPaused as u32)
+        )
+    );
+    assert!(banks_client.get_account(order_pda).await.unwrap().is_none());
 }
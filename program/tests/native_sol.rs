@@ -0,0 +1,234 @@
+mod common;
+
+use bytemuck::Zeroable;
+use common::*;
+use sol_ver::error::SolverError;
+use sol_ver::state::order::{Order, OrderMode};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+const OWNER_LAMPORTS: u64 = 1_000_000_000;
+
+fn native_program_test() -> ProgramTest {
+    let mut program_test = program_test();
+    program_test.add_account(NATIVE_MINT, native_mint_account());
+    program_test
+}
+
+fn wallet() -> Account {
+    Account {
+        lamports: OWNER_LAMPORTS,
+        ..Account::default()
+    }
+}
+
+/// An escrow order selling 100 lamports out of the owner's wallet for `buy_mint`.
+fn native_sell_order(owner: &Pubkey, to_token_account: &Pubkey, buy_mint: &Pubkey) -> Order {
+    Order {
+        from_token_account: to_pinocchio(owner),
+        to_token_account: to_pinocchio(to_token_account),
+        sell_mint: to_pinocchio(&NATIVE_MINT),
+        buy_mint: to_pinocchio(buy_mint),
+        sell_amount: 100,
+        buy_amount: 50,
+        minimun_buy_amount: 45,
+        deadline: u64::MAX,
+        mode: OrderMode::Escrow as u8,
+        native_sell: 1,
+        ..Zeroable::zeroed()
+    }
+}
+
+#[tokio::test]
+async fn test_initialize_native_sell_order_wraps_sol() {
+    let mut program_test = native_program_test();
+
+    let owner = Keypair::new();
+    let buy_mint = Pubkey::new_unique();
+    let to_token_account = Pubkey::new_unique();
+    let (config, _) = find_config_pda();
+    program_test.add_account(
+        config,
+        config_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 0, false),
+    );
+    program_test.add_account(owner.pubkey(), wallet());
+    program_test.add_account(
+        to_token_account,
+        token_account(&buy_mint, &owner.pubkey(), 0),
+    );
+
+    let order = native_sell_order(&owner.pubkey(), &to_token_account, &buy_mint);
+    let order_bytes = order_bytes(&order);
+    let (order_pda, order_bump) = find_order_pda(&owner.pubkey(), &order_bytes);
+    let vault = find_vault(&order_pda, &NATIVE_MINT);
+
+    let mut data = vec![0]; // Instruction::Initialize discriminator
+    data.push(order_bump);
+    data.extend_from_slice(&order_bytes);
+
+    let instruction = Instruction {
+        program_id: Pubkey::new_from_array(sol_ver::ID),
+        accounts: vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(order_pda, false),
+            AccountMeta::new(owner.pubkey(), true), // Rent payer
+            AccountMeta::new(owner.pubkey(), true), // From: the owner's wallet
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(NATIVE_MINT, false),
+            AccountMeta::new_readonly(Pubkey::default(), false), // System Program
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(to_token_account, false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false), // Referral (no fee)
+        ],
+        data,
+    };
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &owner], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    let vault_account = banks_client.get_account(vault).await.unwrap().unwrap();
+    assert_eq!(token_amount(&vault_account.data), 100);
+    assert_eq!(vault_account.lamports, TOKEN_ACCOUNT_RENT + 100);
+}
+
+#[tokio::test]
+async fn test_cancel_native_sell_order_unwraps_sol() {
+    let mut program_test = native_program_test();
+
+    let owner = Keypair::new();
+    program_test.add_account(owner.pubkey(), wallet());
+
+    let order = native_sell_order(
+        &owner.pubkey(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+    );
+    let order_bytes = order_bytes(&order);
+    let (order_pda, order_bump) = find_order_pda(&owner.pubkey(), &order_bytes);
+    let vault = find_vault(&order_pda, &NATIVE_MINT);
+    program_test.add_account(
+        order_pda,
+        Account {
+            lamports: 1_000_000,
+            data: order_account_data(&order, 0),
+            owner: Pubkey::new_from_array(sol_ver::ID),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(vault, native_token_account(&order_pda, 100));
+
+    let mut data = vec![1]; // Instruction::Cancel discriminator
+    data.push(order_bump);
+    data.extend_from_slice(&order_bytes);
+
+    let instruction = Instruction {
+        program_id: Pubkey::new_from_array(sol_ver::ID),
+        accounts: vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(order_pda, false),
+            AccountMeta::new(owner.pubkey(), true), // From: the owner's wallet
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(NATIVE_MINT, false),
+        ],
+        data,
+    };
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &owner], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // The sold SOL, the vault rent and the order rent all return to the owner
+    assert!(banks_client.get_account(vault).await.unwrap().is_none());
+    let owner_account = banks_client
+        .get_account(owner.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        owner_account.lamports,
+        OWNER_LAMPORTS + TOKEN_ACCOUNT_RENT + 100 + 1_000_000
+    );
+}
+
+/// A delegate-mode order buying 100 lamports for the owner's wallet.
+fn setup_native_buy() -> ExecuteFixture {
+    setup_execute(|order| {
+        order.mode = OrderMode::Delegate as u8;
+        order.minimun_buy_amount = 100;
+        order.native_buy = 1;
+    })
+}
+
+#[tokio::test]
+async fn test_execute_native_buy_order_unwraps_sol() {
+    let fixture = setup_native_buy();
+    let owner = fixture.owner.pubkey();
+    let unwrap_account = fixture.route_destination;
+    let instruction = execute_instruction(&fixture, 100);
+    let mut banks_client = execute(fixture, instruction).await.unwrap();
+
+    // The owner receives exactly the bought SOL plus the closed order's rent, the unwrap
+    // account's rent goes back to the solver
+    let owner_account = banks_client.get_account(owner).await.unwrap().unwrap();
+    assert_eq!(owner_account.lamports, OWNER_LAMPORTS + 100 + 1_000_000);
+    assert!(banks_client
+        .get_account(unwrap_account)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_execute_native_buy_order_with_token_2022_sell_mint() {
+    // The unwrap account belongs to SPL Token even when the sell side is Token-2022
+    let fixture = setup_execute_with(
+        ExecuteSetup {
+            token_program: TOKEN_2022_PROGRAM_ID,
+            ..ExecuteSetup::default()
+        },
+        |order| {
+            order.mode = OrderMode::Delegate as u8;
+            order.minimun_buy_amount = 100;
+            order.native_buy = 1;
+        },
+    );
+    let owner = fixture.owner.pubkey();
+    let unwrap_account = fixture.route_destination;
+    let instruction = execute_instruction(&fixture, 100);
+    let mut banks_client = execute(fixture, instruction).await.unwrap();
+
+    let owner_account = banks_client.get_account(owner).await.unwrap().unwrap();
+    assert_eq!(owner_account.lamports, OWNER_LAMPORTS + 100 + 1_000_000);
+    assert!(banks_client
+        .get_account(unwrap_account)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_execute_native_buy_order_rejects_short_fill() {
+    let fixture = setup_native_buy();
+    let instruction = execute_instruction(&fixture, 50);
+    let err = execute_err(fixture, instruction).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::SlippageExceeded as u32)
+        )
+    );
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use bytemuck::Zeroable;
+use common::*;
+use sol_ver::error::SolverError;
+use sol_ver::state::order::{Order, OrderMode};
+use solana_program::pubkey::Pubkey;
+use solana_program_test::*;
+use solana_sdk::{
+    account::Account,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+#[tokio::test]
+async fn test_initialize_escrow_order_covers_transfer_fee() {
+    let mut program_test = program_test();
+
+    let owner = Keypair::new();
+    let sell_mint = Pubkey::new_unique();
+    let buy_mint = Pubkey::new_unique();
+    let from_token_account = Pubkey::new_unique();
+    let to_token_account = Pubkey::new_unique();
+    let (config, _) = find_config_pda();
+
+    program_test.add_account(
+        config,
+        config_account(&Pubkey::new_unique(), &Pubkey::new_unique(), 0, false),
+    );
+    // 1% transfer fee
+    program_test.add_account(sell_mint, token_2022_mint_account(6, 100, 1_000));
+    program_test.add_account(
+        from_token_account,
+        token_2022_account(&sell_mint, &owner.pubkey(), 1000, None),
+    );
+    program_test.add_account(
+        to_token_account,
+        token_account(&buy_mint, &owner.pubkey(), 0),
+    );
+    program_test.add_account(
+        owner.pubkey(),
+        Account {
+            lamports: 1_000_000_000,
+            ..Account::default()
+        },
+    );
+
+    let order = Order {
+        from_token_account: to_pinocchio(&from_token_account),
+        to_token_account: to_pinocchio(&to_token_account),
+        sell_mint: to_pinocchio(&sell_mint),
+        buy_mint: to_pinocchio(&buy_mint),
+        sell_amount: 100,
+        buy_amount: 50,
+        minimun_buy_amount: 45,
+        deadline: u64::MAX,
+        mode: OrderMode::Escrow as u8,
+        ..Zeroable::zeroed()
+    };
+    let order_bytes = order_bytes(&order);
+    let (order_pda, order_bump) = find_order_pda(&owner.pubkey(), &order_bytes);
+    let vault = find_vault_with_program(&order_pda, &sell_mint, &TOKEN_2022_PROGRAM_ID);
+
+    let mut data = vec![0]; // Instruction::Initialize discriminator
+    data.push(order_bump);
+    data.extend_from_slice(&order_bytes);
+
+    let instruction = Instruction {
+        program_id: Pubkey::new_from_array(sol_ver::ID),
+        accounts: vec![
+            AccountMeta::new(owner.pubkey(), true),
+            AccountMeta::new(order_pda, false),
+            AccountMeta::new(owner.pubkey(), true), // Rent payer
+            AccountMeta::new(from_token_account, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(sell_mint, false),
+            AccountMeta::new_readonly(Pubkey::default(), false), // System Program
+            AccountMeta::new_readonly(TOKEN_2022_PROGRAM_ID, false),
+            AccountMeta::new_readonly(ASSOCIATED_TOKEN_PROGRAM_ID, false),
+            AccountMeta::new_readonly(config, false),
+            AccountMeta::new_readonly(to_token_account, false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false), // Referral (no fee)
+        ],
+        data,
+    };
+
+    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+    let mut tx = Transaction::new_with_payer(&[instruction], Some(&payer.pubkey()));
+    tx.sign(&[&payer, &owner], recent_blockhash);
+    banks_client.process_transaction(tx).await.unwrap();
+
+    // The owner pays the fee on top, the vault holds exactly the sell amount
+    let vault_account = banks_client.get_account(vault).await.unwrap().unwrap();
+    assert_eq!(token_amount(&vault_account.data), 100);
+    let from_account = banks_client
+        .get_account(from_token_account)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(token_amount(&from_account.data), 898);
+}
+
+/// A delegate-mode Token-2022 order whose buy mint charges a 1% transfer fee, and whose
+/// owner expects the full buy amount.
+fn setup_execute_token_2022(setup: ExecuteSetup) -> ExecuteFixture {
+    let setup = ExecuteSetup {
+        token_program: TOKEN_2022_PROGRAM_ID,
+        buy_transfer_fee_bps: 100,
+        ..setup
+    };
+    setup_execute_with(setup, |order| {
+        order.mode = OrderMode::Delegate as u8;
+        order.minimun_buy_amount = 100;
+    })
+}
+
+#[tokio::test]
+async fn test_execute_token_2022_order_net_of_transfer_fee() {
+    // 102 minus the 1% fee, rounded up, delivers exactly the buy amount
+    let fixture = setup_execute_token_2022(ExecuteSetup::default());
+    let to_token_account = fixture.to_token_account;
+    let instruction = execute_instruction(&fixture, 102);
+    let mut banks_client = execute(fixture, instruction).await.unwrap();
+
+    assert_eq!(
+        token_balance(&mut banks_client, to_token_account).await,
+        100
+    );
+}
+
+#[tokio::test]
+async fn test_execute_token_2022_order_rejects_fee_eaten_buy_amount() {
+    // The owner only receives 99 of the 100 sent
+    let fixture = setup_execute_token_2022(ExecuteSetup::default());
+    let instruction = execute_instruction(&fixture, 100);
+    let err = execute_err(fixture, instruction).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::SlippageExceeded as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_execute_order_rejects_unknown_token_program() {
+    let fixture = setup_execute_token_2022(ExecuteSetup::default());
+    let mut instruction = execute_instruction(&fixture, 102);
+    instruction.accounts[6] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+
+    let err = execute_err(fixture, instruction).await;
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(SolverError::InvalidTokenProgram as u32)
+        )
+    );
+}
+
+/// An order whose sell mint runs a transfer hook on every transfer.
+fn setup_execute_hooked_sell_mint() -> ExecuteFixture {
+    setup_execute_token_2022(ExecuteSetup {
+        sell_transfer_hook: Some(Pubkey::new_unique()),
+        ..ExecuteSetup::default()
+    })
+}
+
+#[tokio::test]
+async fn test_execute_token_2022_order_passes_hook_accounts() {
+    let fixture = setup_execute_hooked_sell_mint();
+    let solver_sell_token_account = fixture.solver_sell_token_account;
+    // The route's hook accounts reach the sell-side TransferChecked and its hook
+    let instruction = execute_instruction(&fixture, 102);
+    let mut banks_client = execute(fixture, instruction).await.unwrap();
+
+    assert_eq!(
+        token_balance(&mut banks_client, solver_sell_token_account).await,
+        100
+    );
+}
+
+#[tokio::test]
+async fn test_execute_token_2022_order_rejects_missing_hook_accounts() {
+    let mut fixture = setup_execute_hooked_sell_mint();
+    fixture.hook_accounts.clear();
+    let instruction = execute_instruction(&fixture, 102);
+
+    // Token-2022 can't find the hook's validation account
+    let err = execute_err(fixture, instruction).await;
+    assert!(matches!(
+        err,
+        TransactionError::InstructionError(0, InstructionError::Custom(_))
+    ));
+}